@@ -0,0 +1,75 @@
+use fortfetch::i18n::Lang;
+use std::env;
+use std::path::PathBuf;
+
+const HELP: &str = "\
+Usage: fortfetch [OPTIONS]
+
+Options:
+  --json            Print detected system information as JSON
+  --timings         Print how long every detector took to stderr
+  --logo NAME       Built-in logo: auto, fortuna, tux, arch, debian, ubuntu, fedora, alpine
+  --logo-file PATH  Load the logo from a text file with {color} placeholders
+  --no-logo         Print only the information column
+  --lang LANG       Labels language: ru-joke, ru, en (default: from LANG/LC_MESSAGES)
+  -v, --verbose     Same as --timings
+  --debug           Show why a field is unknown next to it (and as \"errors\" in --json)
+  --strict          Exit with an error listing failed detectors instead of showing \"?\"
+  -h, --help        Print this help
+  -V, --version     Print version";
+
+#[derive(Debug, Default)]
+pub struct Args {
+    pub json: bool,
+    pub timings: bool,
+    pub strict: bool,
+    pub debug: bool,
+    pub logo: Option<String>,
+    pub logo_file: Option<PathBuf>,
+    pub no_logo: bool,
+    pub lang: Option<Lang>,
+}
+
+pub enum Parsed {
+    Run(Args),
+    Exit(String),
+}
+
+impl Args {
+    pub fn parse() -> Result<Parsed, String> {
+        let mut args = Args::default();
+
+        let mut argv = env::args().skip(1);
+
+        while let Some(arg) = argv.next() {
+            let mut value = || argv.next().ok_or_else(|| format!("`{}` expects a value", arg));
+
+            match arg.as_str() {
+                "--json" => args.json = true,
+                "--timings" | "-v" | "--verbose" => args.timings = true,
+                "--strict" => args.strict = true,
+                "--debug" => args.debug = true,
+                "--logo" => args.logo = Some(value()?),
+                "--logo-file" => args.logo_file = Some(PathBuf::from(value()?)),
+                "--no-logo" => args.no_logo = true,
+                "--lang" => {
+                    let code = value()?;
+                    let lang = Lang::from_code(&code).ok_or_else(|| {
+                        format!("unknown language `{}`, expected one of {}", code, Lang::CODES.join(", "))
+                    })?;
+                    args.lang = Some(lang);
+                }
+                "-h" | "--help" => return Ok(Parsed::Exit(HELP.to_string())),
+                "-V" | "--version" => {
+                    return Ok(Parsed::Exit(format!(
+                        "fortfetch {}",
+                        env!("CARGO_PKG_VERSION")
+                    )));
+                }
+                other => return Err(format!("unknown argument `{}`, see --help", other)),
+            }
+        }
+
+        Ok(Parsed::Run(args))
+    }
+}
@@ -0,0 +1,58 @@
+//! Inline usage bars like `[████░░░░░░]`, colored by how close the value is to trouble
+
+use crate::config::{BarConfig, Colors};
+use fortfetch::module::Gauge;
+use std::env;
+
+/// Terminals that cannot be trusted with block characters: `TERM=dumb` or a non-UTF-8 locale
+fn ascii_only() -> bool {
+    if env::var("TERM").is_ok_and(|term| term == "dumb") {
+        return true;
+    }
+
+    // the first locale variable that is set wins, like in setlocale(3)
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .find_map(|key| env::var(key).ok().filter(|value| !value.is_empty()));
+    match locale {
+        Some(locale) => {
+            let locale = locale.to_ascii_lowercase();
+            !(locale.contains("utf-8") || locale.contains("utf8"))
+        }
+        None => true,
+    }
+}
+
+/// `gauge` as a bar ending in the value color, so the text after it keeps its color
+pub fn render(gauge: Gauge, config: &BarConfig, colors: &Colors) -> String {
+    let percent = gauge.percent.clamp(0.0, 100.0);
+    let filled = ((percent / 100.0) * config.width as f64).round() as usize;
+
+    // a battery is in trouble when it runs empty, memory when it fills up
+    let pressure = if gauge.higher_is_better {
+        100.0 - percent
+    } else {
+        percent
+    };
+    let color = if pressure >= config.critical as f64 {
+        &colors.bar_critical
+    } else if pressure >= config.warning as f64 {
+        &colors.bar_warning
+    } else {
+        &colors.bar_ok
+    };
+
+    let (fill, empty) = if config.ascii.unwrap_or_else(ascii_only) {
+        (&config.ascii_fill, &config.ascii_empty)
+    } else {
+        (&config.fill, &config.empty)
+    };
+
+    format!(
+        "[{}{}{}{}]",
+        color,
+        fill.repeat(filled),
+        colors.value,
+        empty.repeat(config.width - filled)
+    )
+}
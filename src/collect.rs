@@ -0,0 +1,107 @@
+use crate::Error;
+use std::fmt;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How a single detector went and how long it took
+#[derive(Debug, Clone)]
+pub struct Timing {
+    pub name: &'static str,
+    pub elapsed: Duration,
+    pub status: Status,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    Detected,
+    /// The detector finished without a value, and this is why
+    Failed(Error),
+    /// The detector did not finish before the deadline and its value was dropped
+    TimedOut,
+    /// The detector panicked
+    Crashed,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Status::Detected => f.write_str("ok"),
+            Status::Failed(err) => err.fmt(f),
+            Status::TimedOut => f.write_str("timed out"),
+            Status::Crashed => f.write_str("crashed"),
+        }
+    }
+}
+
+impl Timing {
+    pub fn failed(&self) -> bool {
+        self.status != Status::Detected
+    }
+}
+
+/// A detector running on its own thread
+pub(crate) struct Pending<T> {
+    name: &'static str,
+    receiver: Receiver<(T, Duration)>,
+}
+
+pub(crate) fn spawn<T, F>(name: &'static str, detect: F) -> Pending<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        let started = Instant::now();
+        let value = detect();
+        // the receiver is gone if we missed the deadline
+        let _ = sender.send((value, started.elapsed()));
+    });
+
+    Pending { name, receiver }
+}
+
+/// Shared time limit for a batch of detectors started together
+pub(crate) struct Deadline {
+    started: Instant,
+    at: Instant,
+    timings: Vec<Timing>,
+}
+
+impl Deadline {
+    pub(crate) fn after(timeout: Duration) -> Deadline {
+        let started = Instant::now();
+
+        Deadline {
+            started,
+            at: started + timeout,
+            timings: Vec::new(),
+        }
+    }
+
+    /// Block until the detector finishes or the deadline passes; a stuck detector is left behind
+    pub(crate) fn wait<T>(&mut self, pending: Pending<Result<T, Error>>) -> Option<T> {
+        let remaining = self.at.saturating_duration_since(Instant::now());
+
+        let (value, elapsed, status) = match pending.receiver.recv_timeout(remaining) {
+            Ok((Ok(value), elapsed)) => (Some(value), elapsed, Status::Detected),
+            Ok((Err(err), elapsed)) => (None, elapsed, Status::Failed(err)),
+            Err(RecvTimeoutError::Timeout) => (None, self.started.elapsed(), Status::TimedOut),
+            // the sender was dropped without sending, so the thread unwound
+            Err(RecvTimeoutError::Disconnected) => (None, self.started.elapsed(), Status::Crashed),
+        };
+
+        self.timings.push(Timing {
+            name: pending.name,
+            elapsed,
+            status,
+        });
+        value
+    }
+
+    pub(crate) fn into_timings(self) -> Vec<Timing> {
+        self.timings
+    }
+}
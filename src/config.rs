@@ -1,3 +1,11 @@
+use fortfetch::i18n::{Lang, Messages};
+use fortfetch::module::{self, Module};
+use fortfetch::{CpuUsageSettings, DiskSettings, Filter, SensorSettings, Settings, Units};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use std::{env, fmt, fs};
+
 pub const BLACK: &str = "\x1b[0;30m";
 pub const DARK_GRAY: &str = "\x1b[1;30m";
 pub const LIGHT_GRAY: &str = "\x1b[0;37m";
@@ -14,34 +22,456 @@ pub const NC: &str = "\x1b[0m";
 pub const GAP: usize = 4;
 pub const MAX_KEY_LENGTH: usize = 22;
 
-/// Print ASCII logo with colors
-pub fn get_logo() -> Vec<String> {
-    let accent = LIGHT_BLUE;
-    let nc = NC;
-
-    vec![
-        format!("        {accent}░ ░░░░                            ░░░░░░{nc}"),
-        format!("        {accent}░░░░░░                            ░░░░░░{nc}"),
-        format!("        {accent}░░░▓▓░░░░   ░░░░░░░░░░░░░░░░░  ░░░░▒▒░░{nc}"),
-        format!("         {accent}░░▓▓▓▓▓▓░░░░░▒▒▒▒▒▒▒▒▒▒▒▒░░░░░▒▒▒▒▒▒░░{nc}"),
-        format!("           {accent}░▓▓▓▓▓▓▓▓▓░░░▒▒▒▒▒▒▒▒░░░▒▒▒▒▒▒▒▒▒▒░{nc}"),
-        format!("           {accent}░░▓▓▓▓▓▓▓▓▓▓▒░░▒▒▒▒░░▒▒▒▒▒▒▒▒▒▒▒░░{nc}"),
-        format!("        {accent}░░░▒▓▓░░░░░░░░░▓▓░░▒▒░░▒▒░░░░░░░░░▒▒░░░░{nc}"),
-        format!("        {accent}░░▓▓▓░   ░██░░░░▓▓░░░▒▒▒░░▒░██░░░░░▒▒▒░░{nc}"),
-        format!("        {accent}░░▓▓░░ ░░██░░░█░░▓▓░░▒▒░░▒░░░██░  ░░▒▒░{nc}"),
-        format!("        {accent}░░▓▓░  ░░███▓█▓░░▓▓░░▒▒░░██▓███░░ ░░▒▒░░{nc}"),
-        format!("        {accent}░░▓▓░░   ░░█▓░░░░▓▓░░▒▒░░░░▓█░░   ░░▒▒░░{nc}"),
-        format!("         {accent}░░▓▓░░░░  ░░░░▒▓▓░░░░▒▒░░░░░░  ░░░░▒▒░░░{nc}"),
-        format!("        {accent}░░░░▓▓▓▓░░░░░▓▓▓▒░░▒▒░░▒▒▒▒░░░░░▒▒▒▒░░░░{nc}"),
-        format!("           {accent}░░░░▓▓▓▓▓▓▒░░░░░▒▒░░░░░▒▒▒▒▒▒▒░░░░{nc}"),
-        format!("           {accent}░░░▒▒░░░░░▒▒▒▒▒░░░░▒▒▒▒▒░░░░░▒▒░░░{nc}"),
-        format!("           {accent}░░░░░▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒░░░░░{nc}"),
-        format!("              {accent}░░░░▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒░░░░{nc}"),
-        format!("                 {accent}░░▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒░░░{nc}"),
-        format!("                 {accent}░░░░▒▒▒▒▒▒▒▒▒▒▒▒▒▒░░░░{nc}"),
-        format!("                   {accent}░░░░▒▒▒▒▒▒▒▒▒▒░░░░{nc}"),
-        format!("                      {accent}░░░▒▒▒▒▒▒░░░{nc}"),
-        format!("                      {accent}░░░░░▒▒░░░░░{nc}"),
-        format!("                         {accent}░░░░░░{nc}"),
-    ]
-}
\ No newline at end of file
+/// Named colors accepted in the config file and in logo placeholders
+pub const COLOR_NAMES: [(&str, &str); 11] = [
+    ("black", BLACK),
+    ("dark_gray", DARK_GRAY),
+    ("light_gray", LIGHT_GRAY),
+    ("white", WHITE),
+    ("blue", BLUE),
+    ("light_blue", LIGHT_BLUE),
+    ("cyan", CYAN),
+    ("green", GREEN),
+    ("purple", PURPLE),
+    ("yellow", YELLOW),
+    ("red", RED),
+];
+
+/// ANSI escape sequence, written in the config either as a color name or as raw SGR codes ("1;34")
+#[derive(Debug, Clone)]
+pub struct Color(String);
+
+impl Color {
+    fn new(escape: &str) -> Color {
+        Color(escape.to_string())
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl TryFrom<String> for Color {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        if let Some((_, escape)) = COLOR_NAMES.iter().find(|(name, _)| *name == value) {
+            return Ok(Color::new(escape));
+        }
+
+        let is_sgr = !value.is_empty()
+            && value
+                .split(';')
+                .all(|code| !code.is_empty() && code.chars().all(|c| c.is_ascii_digit()));
+        if is_sgr {
+            return Ok(Color(format!("\x1b[{}m", value)));
+        }
+
+        let names: Vec<&str> = COLOR_NAMES.iter().map(|(name, _)| *name).collect();
+        Err(format!(
+            "unknown color `{}`, expected one of {} or SGR codes like \"1;34\"",
+            value,
+            names.join(", ")
+        ))
+    }
+}
+
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        Color::try_from(value).map_err(serde::de::Error::custom)
+    }
+}
+
+fn deserialize_lang<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Lang>, D::Error> {
+    let code = String::deserialize(deserializer)?;
+    Lang::from_code(&code).map(Some).ok_or_else(|| {
+        serde::de::Error::custom(format!(
+            "unknown language `{}`, expected one of {}",
+            code,
+            Lang::CODES.join(", ")
+        ))
+    })
+}
+
+fn deserialize_units<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Units, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    Units::from_name(&name).ok_or_else(|| {
+        serde::de::Error::custom(format!(
+            "unknown units `{}`, expected one of {}",
+            name,
+            Units::NAMES.join(", ")
+        ))
+    })
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Colors {
+    /// Tree branches and hostname
+    pub accent: Color,
+    /// Module labels and username
+    pub key: Color,
+    /// Module values
+    pub value: Color,
+    /// Line under the header
+    pub separator: Color,
+    pub logo: Color,
+    /// Usage bars below `[bar] warning`
+    pub bar_ok: Color,
+    pub bar_warning: Color,
+    /// Usage bars at `[bar] critical` and above
+    pub bar_critical: Color,
+}
+
+impl Default for Colors {
+    fn default() -> Self {
+        Colors {
+            accent: Color::new(LIGHT_BLUE),
+            key: Color::new(WHITE),
+            value: Color::new(LIGHT_GRAY),
+            separator: Color::new(DARK_GRAY),
+            logo: Color::new(LIGHT_BLUE),
+            bar_ok: Color::new(GREEN),
+            bar_warning: Color::new(YELLOW),
+            bar_critical: Color::new(RED),
+        }
+    }
+}
+
+fn deserialize_modules<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<&'static dyn Module>, D::Error> {
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|name| {
+            module::find(name).ok_or_else(|| {
+                let names: Vec<&str> = module::all().iter().map(|m| m.name()).collect();
+                serde::de::Error::custom(format!(
+                    "unknown module `{}`, expected one of {}",
+                    name,
+                    names.join(", ")
+                ))
+            })
+        })
+        .collect()
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Section {
+    pub name: String,
+    /// Overrides the built-in title for `name`
+    pub title: Option<String>,
+    #[serde(deserialize_with = "deserialize_modules")]
+    pub modules: Vec<&'static dyn Module>,
+}
+
+impl Section {
+    fn new(name: &str, modules: &[&str]) -> Section {
+        Section {
+            name: name.to_string(),
+            title: None,
+            modules: modules
+                .iter()
+                .map(|name| module::find(name).expect("default layout uses built-in modules"))
+                .collect(),
+        }
+    }
+}
+
+/// `[cpu_usage]`
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CpuUsageConfig {
+    /// Milliseconds between the two `/proc/stat` samples; must be less than `timeout_ms`
+    pub interval_ms: u64,
+    /// Show every core after the overall usage
+    pub per_core: bool,
+}
+
+impl Default for CpuUsageConfig {
+    fn default() -> Self {
+        CpuUsageConfig {
+            interval_ms: fortfetch::DEFAULT_CPU_USAGE_INTERVAL.as_millis() as u64,
+            per_core: false,
+        }
+    }
+}
+
+/// `[bar]`
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BarConfig {
+    /// Draw a bar after memory, swap, disk, CPU usage and battery values
+    pub enabled: bool,
+    /// Cells between the brackets
+    pub width: usize,
+    pub fill: String,
+    pub empty: String,
+    /// Used instead of `fill` and `empty` on dumb or non-UTF-8 terminals
+    pub ascii_fill: String,
+    pub ascii_empty: String,
+    /// Force the ASCII characters on or off; detected from `TERM` and the locale when unset
+    pub ascii: Option<bool>,
+    /// Percent from which the bar turns `bar_warning`; batteries count how empty they are
+    pub warning: u8,
+    /// Percent from which the bar turns `bar_critical`
+    pub critical: u8,
+}
+
+impl Default for BarConfig {
+    fn default() -> Self {
+        BarConfig {
+            enabled: false,
+            width: 10,
+            fill: "█".to_string(),
+            empty: "░".to_string(),
+            ascii_fill: "#".to_string(),
+            ascii_empty: "-".to_string(),
+            ascii: None,
+            warning: 70,
+            critical: 90,
+        }
+    }
+}
+
+/// `[temperature]`
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TemperatureConfig {
+    /// Kinds (`cpu`, `gpu`, `nvme`, `other`) or chip names like `acpitz`, in display order
+    pub sensors: Vec<String>,
+}
+
+impl Default for TemperatureConfig {
+    fn default() -> Self {
+        TemperatureConfig {
+            sensors: fortfetch::DEFAULT_SENSORS
+                .iter()
+                .map(|sensor| sensor.to_string())
+                .collect(),
+        }
+    }
+}
+
+/// `[disk]`: `*` patterns; an empty include list lets everything through
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DiskConfig {
+    pub include_mountpoints: Vec<String>,
+    pub exclude_mountpoints: Vec<String>,
+    pub include_fstypes: Vec<String>,
+    /// tmpfs, devtmpfs, overlay and squashfs unless replaced
+    pub exclude_fstypes: Vec<String>,
+    pub include_devices: Vec<String>,
+    pub exclude_devices: Vec<String>,
+}
+
+impl Default for DiskConfig {
+    fn default() -> Self {
+        DiskConfig {
+            include_mountpoints: Vec::new(),
+            exclude_mountpoints: Vec::new(),
+            include_fstypes: Vec::new(),
+            exclude_fstypes: fortfetch::DEFAULT_EXCLUDED_FSTYPES
+                .iter()
+                .map(|fstype| fstype.to_string())
+                .collect(),
+            include_devices: Vec::new(),
+            exclude_devices: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Spaces between the logo and the info column
+    pub gap: usize,
+    /// Upper bound for the label column width
+    pub key_width: usize,
+    /// Milliseconds a detector may run before it is shown as "?"
+    pub timeout_ms: u64,
+    /// Message catalog; taken from the locale environment when unset
+    #[serde(deserialize_with = "deserialize_lang")]
+    pub lang: Option<Lang>,
+    /// Show `user@host` and the separator line
+    pub header: bool,
+    /// Show the color palette line
+    pub palette: bool,
+    /// Built-in logo name, `auto` to pick one for the running distro
+    pub logo: String,
+    /// Custom logo art, takes precedence over `logo`
+    pub logo_file: Option<PathBuf>,
+    /// `binary` (GiB) or `decimal` (GB) for memory, swap and disk sizes
+    #[serde(deserialize_with = "deserialize_units")]
+    pub units: Units,
+    /// Digits after the decimal point in sizes
+    pub precision: usize,
+    pub colors: Colors,
+    pub cpu_usage: CpuUsageConfig,
+    pub disk: DiskConfig,
+    pub temperature: TemperatureConfig,
+    pub bar: BarConfig,
+    #[serde(rename = "section")]
+    pub sections: Vec<Section>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            gap: GAP,
+            key_width: MAX_KEY_LENGTH,
+            timeout_ms: fortfetch::DEFAULT_TIMEOUT.as_millis() as u64,
+            lang: None,
+            header: true,
+            palette: true,
+            logo: crate::logo::DEFAULT_LOGO.to_string(),
+            logo_file: None,
+            units: Units::Binary,
+            precision: 2,
+            colors: Colors::default(),
+            cpu_usage: CpuUsageConfig::default(),
+            disk: DiskConfig::default(),
+            temperature: TemperatureConfig::default(),
+            bar: BarConfig::default(),
+            sections: vec![
+                Section::new(
+                    "system",
+                    &[
+                        "hostname", "distro", "kernel", "uptime", "packages", "shell",
+                    ],
+                ),
+                Section::new(
+                    "hardware",
+                    &["host", "cpu", "gpu", "memory", "swap", "disk"],
+                ),
+                Section::new("performance", &["cpu_usage", "temperature", "load"]),
+                Section::new(
+                    "environment",
+                    &[
+                        "de",
+                        "wm",
+                        "resolution",
+                        "terminal",
+                        "locale",
+                        "processes",
+                        "users",
+                        "network",
+                    ],
+                ),
+                Section::new("power", &["battery"]),
+            ],
+        }
+    }
+}
+
+pub struct ConfigError {
+    path: PathBuf,
+    message: String,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid config {}:\n{}",
+            self.path.display(),
+            self.message
+        )
+    }
+}
+
+impl Config {
+    /// `$XDG_CONFIG_HOME/fortfetch/config.toml`, falling back to `~/.config`
+    pub fn default_path() -> Option<PathBuf> {
+        let base = env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+        Some(base.join("fortfetch").join("config.toml"))
+    }
+
+    /// Load the user config; a missing file means built-in defaults
+    pub fn load() -> Result<Config, ConfigError> {
+        match Self::default_path() {
+            Some(path) if path.exists() => Self::from_file(&path),
+            _ => Ok(Config::default()),
+        }
+    }
+
+    /// Detector and formatting options handed to the library
+    pub fn settings(&self) -> Settings {
+        Settings {
+            timeout: Duration::from_millis(self.timeout_ms),
+            cpu_usage: CpuUsageSettings {
+                interval: Duration::from_millis(self.cpu_usage.interval_ms),
+                per_core: self.cpu_usage.per_core,
+            },
+            disk: DiskSettings {
+                mountpoints: Filter {
+                    include: self.disk.include_mountpoints.clone(),
+                    exclude: self.disk.exclude_mountpoints.clone(),
+                },
+                fstypes: Filter {
+                    include: self.disk.include_fstypes.clone(),
+                    exclude: self.disk.exclude_fstypes.clone(),
+                },
+                devices: Filter {
+                    include: self.disk.include_devices.clone(),
+                    exclude: self.disk.exclude_devices.clone(),
+                },
+            },
+            temperature: SensorSettings {
+                sensors: self.temperature.sensors.clone(),
+            },
+            units: self.units,
+            precision: self.precision,
+        }
+    }
+
+    pub fn from_file(path: &Path) -> Result<Config, ConfigError> {
+        let error = |message: String| ConfigError {
+            path: path.to_path_buf(),
+            message,
+        };
+
+        let contents = fs::read_to_string(path).map_err(|e| error(e.to_string()))?;
+        let config: Config = toml::from_str(&contents).map_err(|e| error(e.to_string()))?;
+        config.validate().map_err(error)?;
+        Ok(config)
+    }
+
+    /// Checks across keys that deserializing alone cannot make
+    fn validate(&self) -> Result<(), String> {
+        // cpu_usage sleeps for the interval inside its detector, which gets timeout_ms in total
+        if self.cpu_usage.interval_ms >= self.timeout_ms {
+            return Err(format!(
+                "cpu_usage.interval_ms ({}) must be less than timeout_ms ({}), \
+                 or the CPU usage line always times out",
+                self.cpu_usage.interval_ms, self.timeout_ms
+            ));
+        }
+        Ok(())
+    }
+}
+
+pub fn section_title<'a>(section: &'a Section, messages: &'static Messages) -> &'a str {
+    if let Some(title) = &section.title {
+        return title;
+    }
+
+    match section.name.as_str() {
+        "system" => messages.system,
+        "hardware" => messages.hardware,
+        "performance" => messages.performance,
+        "environment" => messages.environment,
+        "power" => messages.power,
+        other => other,
+    }
+}
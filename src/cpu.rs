@@ -0,0 +1,408 @@
+//! Processor model, topology, frequency and caches from `/proc/cpuinfo` and sysfs
+
+use crate::error::{Error, Result, read};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+const SYS_CPU: &str = "/sys/devices/system/cpu";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cpu {
+    /// e.g. "Intel Core i7-8550U", "BCM2711" or "Cortex-A76 + Cortex-A55"
+    pub model: String,
+    /// Physical packages
+    pub sockets: usize,
+    /// Physical cores over all sockets
+    pub cores: usize,
+    /// Logical CPUs the kernel has online
+    pub threads: usize,
+    /// Highest `cpuinfo_max_freq` over all cores, in MHz
+    pub max_frequency: Option<u32>,
+    /// Average current frequency over all cores, in MHz
+    pub current_frequency: Option<u32>,
+    /// One entry per distinct cache, L1 first
+    pub caches: Vec<Cache>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheKind {
+    Data,
+    Instruction,
+    Unified,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cache {
+    pub level: u8,
+    pub kind: CacheKind,
+    /// Size of one instance, in bytes
+    pub size: u64,
+    /// How many separate instances there are, e.g. one L1d per core
+    pub count: usize,
+}
+
+pub fn get_cpu() -> Result<Cpu> {
+    let cpuinfo = read("/proc/cpuinfo")?;
+    let model = model_name(&cpuinfo).ok_or_else(|| {
+        Error::parse(
+            "/proc/cpuinfo",
+            "no `model name`, `Hardware` or `uarch` line",
+        )
+    })?;
+
+    let cpus = online_cpus();
+    let (sockets, cores, threads) =
+        topology(&cpus).unwrap_or_else(|| topology_from_cpuinfo(&cpuinfo));
+    let (max_frequency, current_frequency) = frequencies(&cpus, &cpuinfo);
+
+    Ok(Cpu {
+        model,
+        sockets,
+        cores,
+        threads,
+        max_frequency,
+        current_frequency,
+        caches: caches(&cpus),
+    })
+}
+
+/// Values of `key` in `/proc/cpuinfo`, one per processor for per-core keys
+fn fields<'a>(cpuinfo: &'a str, key: &'a str) -> impl Iterator<Item = &'a str> {
+    cpuinfo.lines().filter_map(move |line| {
+        let (name, value) = line.split_once(':')?;
+        (name.trim() == key)
+            .then(|| value.trim())
+            .filter(|value| !value.is_empty())
+    })
+}
+
+/// x86 has `model name`; ARM and RISC-V kernels often only name the board or the core design
+fn model_name(cpuinfo: &str) -> Option<String> {
+    fields(cpuinfo, "model name")
+        .next()
+        .map(clean_model_name)
+        .or_else(|| fields(cpuinfo, "Hardware").next().map(str::to_string))
+        .or_else(|| fields(cpuinfo, "uarch").next().map(str::to_string))
+        .or_else(soc_from_device_tree)
+        .or_else(|| arm_core_names(cpuinfo))
+}
+
+/// "Intel(R) Core(TM) i7-8550U CPU @ 1.80GHz" becomes "Intel Core i7-8550U"
+fn clean_model_name(name: &str) -> String {
+    let name = name.split(" @ ").next().unwrap_or(name);
+    let name = name
+        .replace("(R)", "")
+        .replace("(r)", "")
+        .replace("(TM)", "")
+        .replace("(tm)", "");
+    // Xeons and older Cores put "CPU" before the model number, e.g. "Xeon(R) CPU E5-2680 v4"
+    let all: Vec<&str> = name.split_whitespace().collect();
+    let mut words: Vec<&str> = all
+        .iter()
+        .enumerate()
+        .filter(|&(at, word)| {
+            *word != "CPU"
+                || all
+                    .get(at + 1)
+                    .is_some_and(|next| !next.contains(|c: char| c.is_ascii_digit()))
+        })
+        .map(|(_, word)| *word)
+        .collect();
+    if words.len() > 1 && words.last() == Some(&"Processor") {
+        words.pop();
+    }
+    words.join(" ")
+}
+
+/// The last `compatible` entry names the SoC, e.g. "raspberrypi,4-model-b\0brcm,bcm2711\0"
+fn soc_from_device_tree() -> Option<String> {
+    let compatible = [
+        "/proc/device-tree/compatible",
+        "/sys/firmware/devicetree/base/compatible",
+    ]
+    .iter()
+    .find_map(|path| fs::read(path).ok())?;
+    let compatible = String::from_utf8_lossy(&compatible);
+    let soc = compatible.split('\0').rfind(|entry| !entry.is_empty())?;
+    let model = soc.split_once(',').map_or(soc, |(_, model)| model);
+    Some(model.to_uppercase())
+}
+
+/// ARM cores by `CPU implementer` and `CPU part`, e.g. "Cortex-A76 + Cortex-A55" on big.LITTLE
+fn arm_core_names(cpuinfo: &str) -> Option<String> {
+    let implementers = fields(cpuinfo, "CPU implementer");
+    let parts = fields(cpuinfo, "CPU part");
+
+    let mut names: Vec<String> = Vec::new();
+    for (implementer, part) in implementers.zip(parts) {
+        let implementer = parse_hex(implementer)?;
+        let part = parse_hex(part)?;
+        let name = match ARM_PARTS
+            .iter()
+            .find(|(i, p, _)| (*i, *p) == (implementer, part))
+        {
+            Some((_, _, name)) => name.to_string(),
+            None => {
+                let vendor = ARM_IMPLEMENTERS
+                    .iter()
+                    .find(|(i, _)| *i == implementer)
+                    .map_or("ARM", |(_, vendor)| vendor);
+                format!("{} 0x{:03x}", vendor, part)
+            }
+        };
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+
+    (!names.is_empty()).then(|| names.join(" + "))
+}
+
+fn parse_hex(value: &str) -> Option<u32> {
+    u32::from_str_radix(value.trim_start_matches("0x"), 16).ok()
+}
+
+const ARM_IMPLEMENTERS: &[(u32, &str)] = &[
+    (0x41, "ARM"),
+    (0x42, "Broadcom"),
+    (0x43, "Cavium"),
+    (0x48, "HiSilicon"),
+    (0x4e, "NVIDIA"),
+    (0x50, "APM"),
+    (0x51, "Qualcomm"),
+    (0x53, "Samsung"),
+    (0x61, "Apple"),
+    (0x6d, "Microsoft"),
+    (0xc0, "Ampere"),
+];
+
+const ARM_PARTS: &[(u32, u32, &str)] = &[
+    (0x41, 0xd03, "Cortex-A53"),
+    (0x41, 0xd04, "Cortex-A35"),
+    (0x41, 0xd05, "Cortex-A55"),
+    (0x41, 0xd07, "Cortex-A57"),
+    (0x41, 0xd08, "Cortex-A72"),
+    (0x41, 0xd09, "Cortex-A73"),
+    (0x41, 0xd0a, "Cortex-A75"),
+    (0x41, 0xd0b, "Cortex-A76"),
+    (0x41, 0xd0c, "Neoverse-N1"),
+    (0x41, 0xd0d, "Cortex-A77"),
+    (0x41, 0xd40, "Neoverse-V1"),
+    (0x41, 0xd41, "Cortex-A78"),
+    (0x41, 0xd44, "Cortex-X1"),
+    (0x41, 0xd46, "Cortex-A510"),
+    (0x41, 0xd47, "Cortex-A710"),
+    (0x41, 0xd48, "Cortex-X2"),
+    (0x41, 0xd49, "Neoverse-N2"),
+    (0x41, 0xd4b, "Cortex-A78C"),
+    (0x41, 0xd4d, "Cortex-A715"),
+    (0x41, 0xd4e, "Cortex-X3"),
+    (0x41, 0xd80, "Cortex-A520"),
+    (0x41, 0xd81, "Cortex-A720"),
+    (0x41, 0xd82, "Cortex-X4"),
+    (0xc0, 0xac3, "Ampere-1"),
+];
+
+/// `/sys/devices/system/cpu/cpuN` for every online CPU; offline ones have no `topology`
+fn online_cpus() -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(SYS_CPU) else {
+        return Vec::new();
+    };
+
+    let mut cpus: Vec<_> = entries
+        .flatten()
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            name.strip_prefix("cpu")
+                .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+        })
+        .map(|entry| entry.path())
+        .filter(|path| path.join("topology").exists())
+        .collect();
+    cpus.sort();
+    cpus
+}
+
+fn read_number<T: FromStr>(path: impl AsRef<Path>) -> Option<T> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+/// Sockets, cores and threads from `topology/physical_package_id` and `core_id`
+fn topology(cpus: &[PathBuf]) -> Option<(usize, usize, usize)> {
+    let mut packages = BTreeSet::new();
+    let mut cores = BTreeSet::new();
+
+    for cpu in cpus {
+        // some ARM kernels report -1 for both, which would make every CPU the same core
+        let package: i64 = read_number(cpu.join("topology/physical_package_id"))?;
+        let core: i64 = read_number(cpu.join("topology/core_id"))?;
+        if core < 0 {
+            return None;
+        }
+        packages.insert(package);
+        cores.insert((package, core));
+    }
+
+    (!cpus.is_empty()).then_some((packages.len(), cores.len(), cpus.len()))
+}
+
+/// Same as [`topology`] for kernels without usable sysfs topology, from `physical id` and
+/// `core id`; without those every CPU counts as a core
+fn topology_from_cpuinfo(cpuinfo: &str) -> (usize, usize, usize) {
+    let threads = fields(cpuinfo, "processor").count().max(1);
+    let packages: BTreeSet<_> = fields(cpuinfo, "physical id").collect();
+    let cores: BTreeSet<_> = fields(cpuinfo, "physical id")
+        .zip(fields(cpuinfo, "core id"))
+        .collect();
+
+    let sockets = packages.len().max(1);
+    let cores = if cores.is_empty() {
+        threads
+    } else {
+        cores.len()
+    };
+    (sockets, cores, threads)
+}
+
+/// Max and average current frequency in MHz; `cpufreq` reports kHz
+fn frequencies(cpus: &[PathBuf], cpuinfo: &str) -> (Option<u32>, Option<u32>) {
+    let khz_to_mhz = |khz: u64| (khz / 1000) as u32;
+
+    let max = cpus
+        .iter()
+        .filter_map(|cpu| read_number(cpu.join("cpufreq/cpuinfo_max_freq")))
+        .max()
+        .map(khz_to_mhz);
+
+    let mut current: Vec<u64> = cpus
+        .iter()
+        .filter_map(|cpu| read_number(cpu.join("cpufreq/scaling_cur_freq")))
+        .collect();
+    if current.is_empty() {
+        // VMs and some ARM boards have no cpufreq, but x86 still reports `cpu MHz`
+        current = fields(cpuinfo, "cpu MHz")
+            .filter_map(|mhz| mhz.parse::<f64>().ok())
+            .map(|mhz| (mhz * 1000.0) as u64)
+            .collect();
+    }
+    let average = (!current.is_empty())
+        .then(|| khz_to_mhz(current.iter().sum::<u64>() / current.len() as u64));
+
+    (max, average)
+}
+
+/// Caches of every CPU, with instances shared by several CPUs counted once
+fn caches(cpus: &[PathBuf]) -> Vec<Cache> {
+    let mut seen = BTreeSet::new();
+    let mut caches: Vec<Cache> = Vec::new();
+
+    for cpu in cpus {
+        let Ok(indexes) = fs::read_dir(cpu.join("cache")) else {
+            continue;
+        };
+        for index in indexes.flatten().map(|entry| entry.path()) {
+            let read_attr = |name: &str| {
+                fs::read_to_string(index.join(name))
+                    .ok()
+                    .map(|value| value.trim().to_string())
+            };
+
+            let (Some(level), Some(kind), Some(size), Some(shared)) = (
+                read_attr("level").and_then(|level| level.parse::<u8>().ok()),
+                read_attr("type").and_then(|kind| match kind.as_str() {
+                    "Data" => Some(CacheKind::Data),
+                    "Instruction" => Some(CacheKind::Instruction),
+                    "Unified" => Some(CacheKind::Unified),
+                    _ => None,
+                }),
+                read_attr("size").and_then(|size| parse_size(&size)),
+                read_attr("shared_cpu_list"),
+            ) else {
+                continue;
+            };
+
+            if !seen.insert((level, kind as u8, shared)) {
+                continue;
+            }
+            match caches
+                .iter_mut()
+                .find(|cache| (cache.level, cache.kind, cache.size) == (level, kind, size))
+            {
+                Some(cache) => cache.count += 1,
+                None => caches.push(Cache {
+                    level,
+                    kind,
+                    size,
+                    count: 1,
+                }),
+            }
+        }
+    }
+
+    caches.sort_by_key(|cache| (cache.level, cache.kind as u8));
+    caches
+}
+
+/// "32K" or "8M", as sysfs writes cache sizes
+fn parse_size(size: &str) -> Option<u64> {
+    let (number, unit) = match size.char_indices().find(|(_, c)| !c.is_ascii_digit()) {
+        Some((at, _)) => size.split_at(at),
+        None => (size, ""),
+    };
+    let multiplier = match unit {
+        "" => 1,
+        "K" => 1024,
+        "M" => 1024 * 1024,
+        "G" => 1024 * 1024 * 1024,
+        _ => return None,
+    };
+    Some(number.parse::<u64>().ok()? * multiplier)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clean_model_name_drops_trademarks_clock_and_filler() {
+        let cases = [
+            (
+                "Intel(R) Core(TM) i7-8550U CPU @ 1.80GHz",
+                "Intel Core i7-8550U",
+            ),
+            (
+                "Intel(R) Xeon(R) CPU E5-2680 v4 @ 2.40GHz",
+                "Intel Xeon E5-2680 v4",
+            ),
+            (
+                "Intel(R) Core(TM)2 Duo CPU     E8400  @ 3.00GHz",
+                "Intel Core2 Duo E8400",
+            ),
+            (
+                "AMD Ryzen 7 5800X 8-Core Processor",
+                "AMD Ryzen 7 5800X 8-Core",
+            ),
+            ("AMD Athlon(tm) II X2 240 Processor", "AMD Athlon II X2 240"),
+            (
+                "13th Gen Intel(R) Core(TM) i5-1340P",
+                "13th Gen Intel Core i5-1340P",
+            ),
+            (
+                "  QEMU Virtual CPU version 2.5+  ",
+                "QEMU Virtual CPU version 2.5+",
+            ),
+        ];
+        for (raw, clean) in cases {
+            assert_eq!(clean_model_name(raw), clean, "{:?}", raw);
+        }
+    }
+
+    #[test]
+    fn clean_model_name_keeps_a_lone_word() {
+        assert_eq!(clean_model_name("Processor"), "Processor");
+        assert_eq!(clean_model_name(""), "");
+    }
+}
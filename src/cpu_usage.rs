@@ -0,0 +1,240 @@
+//! Current CPU load from the difference between two `/proc/stat` samples
+
+use crate::error::{Error, Result, read};
+use std::thread;
+use std::time::Duration;
+
+/// How long [`get_cpu_usage`] waits between samples unless configured otherwise
+pub const DEFAULT_CPU_USAGE_INTERVAL: Duration = Duration::from_millis(200);
+
+#[derive(Debug, Clone)]
+pub struct CpuUsageSettings {
+    /// Time between the two samples; must stay below the detector timeout
+    pub interval: Duration,
+    /// Show every core after the overall figure
+    pub per_core: bool,
+}
+
+impl Default for CpuUsageSettings {
+    fn default() -> Self {
+        CpuUsageSettings {
+            interval: DEFAULT_CPU_USAGE_INTERVAL,
+            per_core: false,
+        }
+    }
+}
+
+/// Jiffies (`USER_HZ` ticks) spent in each state, as on a `cpu` line of `/proc/stat`.
+/// `guest` and `guest_nice` are left out because the kernel already counts them in `user` and `nice`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CpuTimes {
+    pub user: u64,
+    pub nice: u64,
+    pub system: u64,
+    pub idle: u64,
+    /// Idle while waiting for I/O
+    pub iowait: u64,
+    pub irq: u64,
+    pub softirq: u64,
+    /// Taken by the hypervisor for other guests
+    pub steal: u64,
+}
+
+impl CpuTimes {
+    /// Fields after the `cpu`/`cpuN` name; kernels before 2.6.11 stop early, missing ones are zero
+    fn parse(fields: &str) -> Option<CpuTimes> {
+        let mut values = fields
+            .split_whitespace()
+            .map(|value| value.parse::<u64>().ok());
+        let mut next = || values.next().unwrap_or(Some(0));
+
+        Some(CpuTimes {
+            user: next()?,
+            nice: next()?,
+            system: next()?,
+            idle: next()?,
+            iowait: next()?,
+            irq: next()?,
+            softirq: next()?,
+            steal: next()?,
+        })
+    }
+
+    /// Idle time, including time idle while waiting for I/O
+    pub fn idle(&self) -> u64 {
+        self.idle + self.iowait
+    }
+
+    /// Time running anything, interrupts and stolen time included
+    pub fn busy(&self) -> u64 {
+        self.user + self.nice + self.system + self.irq + self.softirq + self.steal
+    }
+
+    pub fn total(&self) -> u64 {
+        self.idle() + self.busy()
+    }
+
+    /// Share of busy time, `None` when no time passed at all
+    pub fn percent(&self) -> Option<f64> {
+        let total = self.total();
+        (total > 0).then(|| self.busy() as f64 * 100.0 / total as f64)
+    }
+
+    fn since(&self, earlier: &CpuTimes) -> CpuTimes {
+        CpuTimes {
+            user: self.user.saturating_sub(earlier.user),
+            nice: self.nice.saturating_sub(earlier.nice),
+            system: self.system.saturating_sub(earlier.system),
+            idle: self.idle.saturating_sub(earlier.idle),
+            iowait: self.iowait.saturating_sub(earlier.iowait),
+            irq: self.irq.saturating_sub(earlier.irq),
+            softirq: self.softirq.saturating_sub(earlier.softirq),
+            steal: self.steal.saturating_sub(earlier.steal),
+        }
+    }
+}
+
+/// Counters since boot: the `cpu` line and one `cpuN` line per online core
+pub fn read_cpu_times() -> Result<(CpuTimes, Vec<CpuTimes>)> {
+    parse_stat(&read("/proc/stat")?)
+}
+
+fn parse_stat(stat: &str) -> Result<(CpuTimes, Vec<CpuTimes>)> {
+    let invalid = |line: &str| Error::parse("/proc/stat", format!("bad line `{}`", line));
+
+    let mut total = None;
+    let mut cores = Vec::new();
+    for line in stat.lines().filter(|line| line.starts_with("cpu")) {
+        let (name, fields) = line.split_once(' ').ok_or_else(|| invalid(line))?;
+        let times = CpuTimes::parse(fields).ok_or_else(|| invalid(line))?;
+        if name == "cpu" {
+            total = Some(times);
+        } else {
+            cores.push(times);
+        }
+    }
+
+    let total = total.ok_or_else(|| Error::parse("/proc/stat", "no aggregate `cpu` line"))?;
+    Ok((total, cores))
+}
+
+/// Jiffies spent in each state during one sampling interval
+#[derive(Debug, Clone, PartialEq)]
+pub struct CpuUsage {
+    pub interval: Duration,
+    /// All cores together
+    pub total: CpuTimes,
+    /// One entry per core present in both samples
+    pub cores: Vec<CpuTimes>,
+}
+
+impl CpuUsage {
+    pub fn percent(&self) -> f64 {
+        self.total.percent().unwrap_or(0.0)
+    }
+
+    pub fn core_percents(&self) -> Vec<f64> {
+        self.cores
+            .iter()
+            .map(|core| core.percent().unwrap_or(0.0))
+            .collect()
+    }
+}
+
+/// Sample `/proc/stat`, sleep for `interval` and sample again
+pub fn get_cpu_usage(interval: Duration) -> Result<CpuUsage> {
+    let (total_before, cores_before) = read_cpu_times()?;
+    thread::sleep(interval);
+    let (total_after, cores_after) = read_cpu_times()?;
+
+    let total = total_after.since(&total_before);
+    if total.total() == 0 {
+        return Err(Error::parse(
+            "/proc/stat",
+            format!("no ticks within {:?}, use a longer interval", interval),
+        ));
+    }
+
+    Ok(CpuUsage {
+        interval,
+        total,
+        cores: cores_after
+            .iter()
+            .zip(&cores_before)
+            .map(|(after, before)| after.since(before))
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STAT: &str = "\
+cpu  10132153 290696 3084719 46828483 16683 0 25195 175 0 0
+cpu0 1393280 32966 572056 13343292 6130 0 17875 100 0 0
+cpu1 1335962 34474 485946 13354412 3467 0 2504 75 0 0
+intr 1462898 0 0 0
+ctxt 115315
+";
+
+    #[test]
+    fn stat_lines_become_cpu_times() {
+        let (total, cores) = parse_stat(STAT).unwrap();
+        assert_eq!(
+            total,
+            CpuTimes {
+                user: 10132153,
+                nice: 290696,
+                system: 3084719,
+                idle: 46828483,
+                iowait: 16683,
+                irq: 0,
+                softirq: 25195,
+                steal: 175,
+            }
+        );
+        assert_eq!(cores.len(), 2);
+        assert_eq!(cores[1].idle, 13354412);
+    }
+
+    #[test]
+    fn old_kernels_leave_the_last_fields_zero() {
+        let (total, _) = parse_stat("cpu 1 2 3 4\n").unwrap();
+        assert_eq!(
+            (total.user, total.idle, total.iowait, total.steal),
+            (1, 4, 0, 0)
+        );
+    }
+
+    #[test]
+    fn bad_stat_is_a_parse_error() {
+        for stat in ["cpu0 1 2 3 4\n", "cpu 1 x 3 4\n", "cpu\n", ""] {
+            assert!(
+                matches!(parse_stat(stat), Err(Error::Parse { .. })),
+                "{:?}",
+                stat
+            );
+        }
+    }
+
+    #[test]
+    fn percent_counts_busy_time_since_the_earlier_sample() {
+        let before = CpuTimes {
+            user: 100,
+            idle: 100,
+            ..CpuTimes::default()
+        };
+        let after = CpuTimes {
+            user: 130,
+            system: 10,
+            idle: 150,
+            iowait: 10,
+            ..CpuTimes::default()
+        };
+        assert_eq!(after.since(&before).percent(), Some(40.0));
+        assert_eq!(before.since(&before).percent(), None);
+        // a counter that went backwards counts as no time rather than wrapping around
+        assert_eq!(before.since(&after).user, 0);
+    }
+}
@@ -0,0 +1,77 @@
+//! The desktop environment of the session and its version
+
+use crate::error::{Result, env_var};
+use crate::version;
+use std::fs;
+
+/// Session files where Plasma writes down its version, X11 and Wayland ones across releases
+const PLASMA_SESSIONS: &[&str] = &[
+    "/usr/share/wayland-sessions/plasma.desktop",
+    "/usr/share/wayland-sessions/plasmawayland.desktop",
+    "/usr/share/xsessions/plasmax11.desktop",
+    "/usr/share/xsessions/plasma.desktop",
+];
+
+/// `XDG_CURRENT_DESKTOP` entries, the names they go by, and the executable asked for a version
+const DESKTOPS: &[(&str, &str, Option<&str>)] = &[
+    ("gnome", "GNOME", Some("gnome-shell")),
+    ("kde", "KDE Plasma", Some("plasmashell")),
+    ("xfce", "Xfce", Some("xfce4-session")),
+    ("x-cinnamon", "Cinnamon", Some("cinnamon")),
+    ("cinnamon", "Cinnamon", Some("cinnamon")),
+    ("mate", "MATE", Some("mate-session")),
+    ("lxqt", "LXQt", Some("lxqt-session")),
+    ("budgie", "Budgie", Some("budgie-desktop")),
+    ("cosmic", "COSMIC", None),
+    ("pantheon", "Pantheon", None),
+    ("deepin", "Deepin", None),
+    ("unity", "Unity", None),
+    ("lxde", "LXDE", None),
+    ("enlightenment", "Enlightenment", None),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DesktopEnvironment {
+    /// e.g. "GNOME", or the variable's value as is for unknown desktops
+    pub name: String,
+    /// e.g. "46.0"
+    pub version: Option<String>,
+}
+
+fn plasma_version() -> Option<String> {
+    PLASMA_SESSIONS.iter().find_map(|path| {
+        let session = fs::read_to_string(path).ok()?;
+        session
+            .lines()
+            .find_map(|line| line.strip_prefix("X-KDE-PluginInfo-Version="))
+            .map(|version| version.trim().to_string())
+    })
+}
+
+pub fn get_desktop_environment() -> Result<DesktopEnvironment> {
+    let value = env_var(&["XDG_CURRENT_DESKTOP", "DESKTOP_SESSION", "GDMSESSION"])?;
+    // "ubuntu:GNOME" lists the desktop a distro's flavour is based on after the flavour
+    let known = value.split(':').find_map(|entry| {
+        let entry = entry.to_ascii_lowercase();
+        DESKTOPS.iter().find(|(id, _, _)| *id == entry)
+    });
+    let Some((id, name, executable)) = known else {
+        return Ok(DesktopEnvironment {
+            name: value,
+            version: None,
+        });
+    };
+
+    let version = match *id {
+        "kde" => plasma_version(),
+        _ => None,
+    }
+    .or_else(|| {
+        let executable = version::find_executable((*executable)?)?;
+        version::probe(&executable, &["--version"])
+    });
+    Ok(DesktopEnvironment {
+        name: name.to_string(),
+        version,
+    })
+}
@@ -0,0 +1,226 @@
+//! Mounted filesystems from `/proc/self/mountinfo`, sized with `statvfs(3)`
+
+use crate::Usage;
+use crate::error::{Error, Result, read};
+use std::collections::HashSet;
+use std::ffi::CString;
+use std::path::Path;
+
+/// Filesystem types hidden unless the config says otherwise: RAM-backed, layered or read-only images
+pub const DEFAULT_EXCLUDED_FSTYPES: &[&str] = &["tmpfs", "devtmpfs", "overlay", "squashfs"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Disk {
+    /// Where it is mounted, e.g. "/home"
+    pub mountpoint: String,
+    /// Mount source, e.g. "/dev/nvme0n1p2"
+    pub device: String,
+    /// e.g. "ext4" or "btrfs"
+    pub fstype: String,
+    pub usage: Usage,
+}
+
+/// Shell-style patterns where `*` matches any run of characters, e.g. "/media/*"
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    /// When not empty, only matching values are shown
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+impl Filter {
+    fn allows(&self, value: &str) -> bool {
+        let matches = |pattern: &String| glob_match(pattern, value);
+        (self.include.is_empty() || self.include.iter().any(matches))
+            && !self.exclude.iter().any(matches)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DiskSettings {
+    pub mountpoints: Filter,
+    pub fstypes: Filter,
+    pub devices: Filter,
+}
+
+impl Default for DiskSettings {
+    fn default() -> Self {
+        DiskSettings {
+            mountpoints: Filter::default(),
+            fstypes: Filter {
+                include: Vec::new(),
+                exclude: DEFAULT_EXCLUDED_FSTYPES
+                    .iter()
+                    .map(|fstype| fstype.to_string())
+                    .collect(),
+            },
+            devices: Filter::default(),
+        }
+    }
+}
+
+fn glob_match(pattern: &str, value: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == value,
+        Some((prefix, rest)) => {
+            let Some(value) = value.strip_prefix(prefix) else {
+                return false;
+            };
+            // let the star swallow 0..=len characters and match the rest of the pattern
+            value
+                .char_indices()
+                .map(|(at, _)| at)
+                .chain([value.len()])
+                .any(|at| glob_match(rest, &value[at..]))
+        }
+    }
+}
+
+/// One line of `/proc/self/mountinfo`:
+/// `36 35 98:0 /mnt1 /mnt2 rw,noatime master:1 - ext3 /dev/root rw,errors=continue`
+struct MountInfo {
+    device_number: String,
+    root: String,
+    mountpoint: String,
+    fstype: String,
+    source: String,
+}
+
+impl MountInfo {
+    fn parse(line: &str) -> Option<MountInfo> {
+        let (mount, superblock) = line.split_once(" - ")?;
+        let mut mount = mount.split(' ');
+        let mut superblock = superblock.split(' ');
+
+        Some(MountInfo {
+            device_number: mount.nth(2)?.to_string(),
+            root: unescape(mount.next()?),
+            mountpoint: unescape(mount.next()?),
+            fstype: superblock.next()?.to_string(),
+            source: unescape(superblock.next()?),
+        })
+    }
+}
+
+/// mountinfo writes space, tab, newline and backslash as `\040`-style octal escapes
+fn unescape(field: &str) -> String {
+    let mut out = String::with_capacity(field.len());
+    let mut rest = field;
+    while let Some(at) = rest.find('\\') {
+        out.push_str(&rest[..at]);
+        let code = rest
+            .get(at + 1..at + 4)
+            .filter(|octal| octal.bytes().all(|b| (b'0'..=b'7').contains(&b)))
+            .and_then(|octal| u8::from_str_radix(octal, 8).ok());
+        match code {
+            Some(byte) => {
+                out.push(byte as char);
+                rest = &rest[at + 4..];
+            }
+            None => {
+                out.push('\\');
+                rest = &rest[at + 1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Used and total space as `df` counts it; `None` for pseudo filesystems with no blocks
+fn statvfs(mountpoint: &str) -> Option<Usage> {
+    let path = CString::new(mountpoint).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 || stat.f_blocks == 0 {
+        return None;
+    }
+
+    let block = stat.f_frsize as u64;
+    Some(Usage {
+        used: (stat.f_blocks as u64).saturating_sub(stat.f_bfree as u64) * block,
+        total: stat.f_blocks as u64 * block,
+    })
+}
+
+/// Every real filesystem that passes the filters, in mount order
+pub fn get_disks(settings: &DiskSettings) -> Result<Vec<Disk>> {
+    let mountinfo = read("/proc/self/mountinfo")?;
+    let mut seen = HashSet::new();
+    let mut disks = Vec::new();
+
+    for mount in mountinfo.lines().filter_map(MountInfo::parse) {
+        if !settings.mountpoints.allows(&mount.mountpoint)
+            || !settings.fstypes.allows(&mount.fstype)
+            || !settings.devices.allows(&mount.source)
+        {
+            continue;
+        }
+        // containers bind-mount single files like /etc/hosts
+        if !Path::new(&mount.mountpoint).is_dir() {
+            continue;
+        }
+        // the same filesystem bound a second time; btrfs subvolumes differ in `root`
+        if !seen.insert((mount.device_number.clone(), mount.root.clone())) {
+            continue;
+        }
+        let Some(usage) = statvfs(&mount.mountpoint) else {
+            continue;
+        };
+
+        disks.push(Disk {
+            mountpoint: mount.mountpoint,
+            device: mount.source,
+            fstype: mount.fstype,
+            usage,
+        });
+    }
+
+    if disks.is_empty() {
+        return Err(Error::NotFound(
+            "mounted filesystem passing the disk filters",
+        ));
+    }
+    Ok(disks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unescape_decodes_octal_escapes() {
+        assert_eq!(unescape("/media/My\\040Disk"), "/media/My Disk");
+        assert_eq!(unescape("a\\011b\\012c\\134d"), "a\tb\nc\\d");
+        assert_eq!(unescape("\\040\\040"), "  ");
+    }
+
+    #[test]
+    fn unescape_keeps_backslashes_that_are_not_escapes() {
+        assert_eq!(unescape("a\\"), "a\\");
+        assert_eq!(unescape("a\\04"), "a\\04");
+        assert_eq!(unescape("a\\089"), "a\\089");
+        assert_eq!(unescape("a\\+12"), "a\\+12");
+        assert_eq!(unescape("a\\400"), "a\\400");
+        assert_eq!(unescape("дом\\040"), "дом ");
+    }
+
+    #[test]
+    fn glob_match_stars() {
+        assert!(glob_match("/snap/*", "/snap/core/123"));
+        assert!(glob_match("/snap/*", "/snap/"));
+        assert!(!glob_match("/snap/*", "/snap"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("/run/*/gvfs", "/run/user/1000/gvfs"));
+        assert!(glob_match("*a*b*", "xaybz"));
+        assert!(!glob_match("*a*b*", "xbya"));
+        assert!(glob_match("/mnt/диск*", "/mnt/диск2"));
+    }
+
+    #[test]
+    fn glob_match_without_stars_is_equality() {
+        assert!(glob_match("/boot", "/boot"));
+        assert!(!glob_match("/boot", "/boot/efi"));
+        assert!(glob_match("", ""));
+        assert!(!glob_match("", "/"));
+    }
+}
@@ -0,0 +1,320 @@
+//! Connected monitors from DRM connectors in sysfs and their EDID
+
+use crate::drm;
+use crate::error::{Error, Result, read_dir};
+use std::fs;
+use std::path::Path;
+
+const DRM: &str = "/sys/class/drm";
+
+/// The fixed header every EDID base block starts with
+const EDID_HEADER: [u8; 8] = [0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00];
+const EDID_BLOCK: usize = 128;
+/// Tag of a CTA-861 extension block, which can carry more detailed timings
+const CTA_EXTENSION: u8 = 0x02;
+
+/// Full names for common PNP manufacturer IDs; the rest are shown as the three letters
+const MANUFACTURERS: &[(&str, &str)] = &[
+    ("ACR", "Acer"),
+    ("AOC", "AOC"),
+    ("APP", "Apple"),
+    ("AUO", "AU Optronics"),
+    ("AUS", "ASUS"),
+    ("BNQ", "BenQ"),
+    ("BOE", "BOE"),
+    ("CMN", "Innolux"),
+    ("DEL", "Dell"),
+    ("ENC", "EIZO"),
+    ("GBT", "Gigabyte"),
+    ("GSM", "LG"),
+    ("HPN", "HP"),
+    ("HWP", "HP"),
+    ("IVM", "Iiyama"),
+    ("LEN", "Lenovo"),
+    ("LGD", "LG Display"),
+    ("MSI", "MSI"),
+    ("NEC", "NEC"),
+    ("PHL", "Philips"),
+    ("SAM", "Samsung"),
+    ("SDC", "Samsung Display"),
+    ("SHP", "Sharp"),
+    ("SNY", "Sony"),
+    ("VSC", "ViewSonic"),
+    ("XMI", "Xiaomi"),
+];
+
+/// A detailed timing from the EDID
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mode {
+    pub width: u32,
+    pub height: u32,
+    /// Vertical refresh in Hz, e.g. 59.95
+    pub refresh: f64,
+    pub interlaced: bool,
+}
+
+/// What a monitor says about itself in its EDID
+#[derive(Debug, Clone, PartialEq)]
+pub struct Edid {
+    /// Three-letter PNP ID, e.g. "DEL"
+    pub manufacturer_id: String,
+    pub product_code: u16,
+    /// Monitor name descriptor, falling back to the free-form text laptop panels put there
+    pub name: Option<String>,
+    pub serial: Option<String>,
+    /// Year of manufacture, or the model year when the week byte is 0xff
+    pub year: u16,
+    /// Image size in millimetres; projectors and some TVs leave it out
+    pub width_mm: Option<u32>,
+    pub height_mm: Option<u32>,
+    /// Every detailed timing, preferred mode first
+    pub modes: Vec<Mode>,
+}
+
+impl Edid {
+    /// Full name where the ID is a known one, e.g. "Dell" for "DEL"
+    pub fn manufacturer(&self) -> &str {
+        MANUFACTURERS
+            .iter()
+            .find(|(id, _)| *id == self.manufacturer_id)
+            .map_or(&self.manufacturer_id, |(_, name)| name)
+    }
+
+    pub fn preferred_mode(&self) -> Option<&Mode> {
+        self.modes.first()
+    }
+}
+
+/// An 18-byte detailed timing descriptor, `None` for the display descriptors sharing its slots
+fn detailed_timing(descriptor: &[u8]) -> Option<(Mode, u32, u32)> {
+    let pixel_clock = u16::from_le_bytes([descriptor[0], descriptor[1]]) as u64 * 10_000;
+    if pixel_clock == 0 {
+        return None;
+    }
+
+    let high = |byte: u8, shift: u32| ((byte >> shift) & 0x0f) as u32;
+    let width = descriptor[2] as u32 | high(descriptor[4], 4) << 8;
+    let h_blank = descriptor[3] as u32 | high(descriptor[4], 0) << 8;
+    let height = descriptor[5] as u32 | high(descriptor[7], 4) << 8;
+    let v_blank = descriptor[6] as u32 | high(descriptor[7], 0) << 8;
+    let width_mm = descriptor[12] as u32 | high(descriptor[14], 4) << 8;
+    let height_mm = descriptor[13] as u32 | high(descriptor[14], 0) << 8;
+    let interlaced = descriptor[17] & 0x80 != 0;
+
+    let total = (width + h_blank) as u64 * (height + v_blank) as u64;
+    if total == 0 {
+        return None;
+    }
+    // interlaced timings describe one field of `height` lines, and their rate is the field rate
+    let mode = Mode {
+        width,
+        height: if interlaced { height * 2 } else { height },
+        refresh: pixel_clock as f64 / total as f64,
+        interlaced,
+    };
+    Some((mode, width_mm, height_mm))
+}
+
+/// Text of a display descriptor: up to 13 bytes, ended by a newline and padded with spaces
+fn descriptor_text(descriptor: &[u8]) -> Option<String> {
+    let text = &descriptor[5..18];
+    let end = text.iter().position(|&c| c == b'\n').unwrap_or(text.len());
+    let text = String::from_utf8_lossy(&text[..end]).trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+/// Parse an EDID 1.3/1.4 blob as found in `/sys/class/drm/card0-eDP-1/edid`
+pub fn parse_edid(bytes: &[u8]) -> Result<Edid> {
+    let invalid = |reason: &str| Error::parse("EDID", reason);
+    if bytes.len() < EDID_BLOCK {
+        return Err(invalid("shorter than one 128-byte block"));
+    }
+    let base = &bytes[..EDID_BLOCK];
+    if base[..8] != EDID_HEADER {
+        return Err(invalid("no 00 ff ff ff ff ff ff 00 header"));
+    }
+    if base.iter().fold(0u8, |sum, &byte| sum.wrapping_add(byte)) != 0 {
+        return Err(invalid("bad checksum"));
+    }
+
+    // three 5-bit letters, 1 = 'A', big-endian
+    let id = u16::from_be_bytes([base[8], base[9]]);
+    let manufacturer_id: String = [10, 5, 0]
+        .iter()
+        .map(|shift| (b'A' - 1 + ((id >> shift) & 0x1f) as u8) as char)
+        .collect();
+
+    let mut edid = Edid {
+        manufacturer_id,
+        product_code: u16::from_le_bytes([base[10], base[11]]),
+        name: None,
+        serial: None,
+        year: 1990 + base[17] as u16,
+        width_mm: None,
+        height_mm: None,
+        modes: Vec::new(),
+    };
+    let mut text = None;
+    let mut timing_size = None;
+
+    for descriptor in base[54..126].chunks_exact(18) {
+        if let Some((mode, width_mm, height_mm)) = detailed_timing(descriptor) {
+            if timing_size.is_none() && width_mm > 0 && height_mm > 0 {
+                timing_size = Some((width_mm, height_mm));
+            }
+            edid.modes.push(mode);
+            continue;
+        }
+        match descriptor[3] {
+            0xfc => edid.name = descriptor_text(descriptor),
+            0xff => edid.serial = descriptor_text(descriptor),
+            // panels put their vendor in the first text and the model in the last
+            0xfe => text = descriptor_text(descriptor).or(text),
+            _ => {}
+        }
+    }
+    edid.name = edid.name.or(text);
+
+    // the detailed timing has the size in mm, the basic parameters only in cm
+    let (width_mm, height_mm) = timing_size.unwrap_or((base[21] as u32 * 10, base[22] as u32 * 10));
+    edid.width_mm = (width_mm > 0).then_some(width_mm);
+    edid.height_mm = (height_mm > 0).then_some(height_mm);
+
+    // CTA-861 extensions list further timings from the offset in byte 2
+    for block in bytes[EDID_BLOCK..].chunks_exact(EDID_BLOCK) {
+        let offset = block[2] as usize;
+        if block[0] != CTA_EXTENSION || offset < 4 {
+            continue;
+        }
+        edid.modes.extend(
+            block[offset.min(127)..127]
+                .chunks_exact(18)
+                .map_while(detailed_timing)
+                .map(|(mode, _, _)| mode),
+        );
+    }
+
+    Ok(edid)
+}
+
+/// A monitor plugged into a DRM connector
+#[derive(Debug, Clone, PartialEq)]
+pub struct Display {
+    /// Connector without the card prefix, e.g. "eDP-1" or "HDMI-A-1"
+    pub connector: String,
+    /// `None` when the connector has no readable EDID, as with some KVMs and virtual GPUs
+    pub edid: Option<Edid>,
+    /// The mode in use when [`Display::current`] is set, otherwise the monitor's native one
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub refresh: Option<f64>,
+    /// Whether the mode was read from the CRTC driving the monitor. Without access to
+    /// `/dev/dri`, only the preferred mode from sysfs and the EDID is known
+    pub current: bool,
+}
+
+impl Display {
+    /// Screen diagonal in inches, from the EDID image size
+    pub fn diagonal(&self) -> Option<f64> {
+        let edid = self.edid.as_ref()?;
+        let (width, height) = (edid.width_mm? as f64, edid.height_mm? as f64);
+        Some((width * width + height * height).sqrt() / 25.4)
+    }
+}
+
+/// `1920x1080`, or `1920x1080i` for interlaced modes
+fn parse_mode(mode: &str) -> Option<(u32, u32)> {
+    let (width, height) = mode.split_once('x')?;
+    let height = height.trim_end_matches(|c: char| !c.is_ascii_digit());
+    Some((width.parse().ok()?, height.parse().ok()?))
+}
+
+fn display(connector: &Path, name: String, active: Option<&Mode>) -> Display {
+    let edid = fs::read(connector.join("edid"))
+        .ok()
+        .filter(|bytes| !bytes.is_empty())
+        .and_then(|bytes| parse_edid(&bytes).ok());
+
+    if let Some(mode) = active {
+        return Display {
+            connector: name,
+            edid,
+            width: Some(mode.width),
+            height: Some(mode.height),
+            refresh: Some(mode.refresh),
+            current: true,
+        };
+    }
+
+    // the kernel lists the preferred mode first
+    let listed = fs::read_to_string(connector.join("modes"))
+        .ok()
+        .and_then(|modes| parse_mode(modes.lines().next()?));
+    let preferred = edid.as_ref().and_then(Edid::preferred_mode);
+    let (width, height) = match (listed, preferred) {
+        (Some((width, height)), _) => (Some(width), Some(height)),
+        (None, Some(mode)) => (Some(mode.width), Some(mode.height)),
+        (None, None) => (None, None),
+    };
+    let refresh = edid.as_ref().and_then(|edid| {
+        edid.modes
+            .iter()
+            .find(|mode| Some(mode.width) == width && Some(mode.height) == height)
+            .map(|mode| mode.refresh)
+    });
+
+    Display {
+        connector: name,
+        edid,
+        width,
+        height,
+        refresh,
+        current: false,
+    }
+}
+
+/// Every connected monitor, on any GPU, in connector order
+pub fn get_displays() -> Result<Vec<Display>> {
+    let mut connectors: Vec<_> = read_dir(DRM)?
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            // card0-HDMI-A-1, not card0 or renderD128
+            let (card, connector) = name.split_once('-')?;
+            card.starts_with("card")
+                .then(|| (entry.path(), card.to_string(), connector.to_string()))
+        })
+        .filter(|(path, _, _)| {
+            fs::read_to_string(path.join("status")).is_ok_and(|status| status.trim() == "connected")
+        })
+        .collect();
+    connectors.sort();
+
+    if connectors.is_empty() {
+        return Err(Error::NotFound("connected monitor in /sys/class/drm"));
+    }
+    let mut cards: Vec<&str> = connectors
+        .iter()
+        .map(|(_, card, _)| card.as_str())
+        .collect();
+    cards.dedup();
+    let active: Vec<(&str, String, Mode)> = cards
+        .into_iter()
+        .flat_map(|card| {
+            drm::active_modes(card)
+                .into_iter()
+                .map(move |(connector, mode)| (card, connector, mode))
+        })
+        .collect();
+
+    Ok(connectors
+        .iter()
+        .map(|(path, card, name)| {
+            let mode = active
+                .iter()
+                .find(|(on, connector, _)| on == card && connector == name)
+                .map(|(_, _, mode)| mode);
+            display(path, name.clone(), mode)
+        })
+        .collect())
+}
@@ -0,0 +1,226 @@
+//! The mode each connector is driven at right now, asked from the kernel's DRM device.
+//!
+//! sysfs only lists the modes a monitor supports; the one in use lives in the CRTC the
+//! connector's encoder feeds from. These are the read-only calls any client may make without
+//! being DRM master. Opened on a bare console we do become master, and then a connector query
+//! with no room for modes makes the kernel probe the monitor again, EDID read and all; like
+//! libdrm's `drmModeGetConnectorCurrent`, we always offer room for one.
+
+use crate::display::Mode;
+use std::fs::File;
+use std::mem::size_of;
+use std::os::fd::AsRawFd;
+
+/// Names the kernel gives connector types, indexed by `DRM_MODE_CONNECTOR_*`, as in `card0-HDMI-A-1`
+const CONNECTOR_TYPES: &[&str] = &[
+    "Unknown",
+    "VGA",
+    "DVI-I",
+    "DVI-D",
+    "DVI-A",
+    "Composite",
+    "SVIDEO",
+    "LVDS",
+    "Component",
+    "DIN",
+    "DP",
+    "HDMI-A",
+    "HDMI-B",
+    "TV",
+    "eDP",
+    "Virtual",
+    "DSI",
+    "DPI",
+    "Writeback",
+    "SPI",
+    "USB",
+];
+
+const DRM_MODE_FLAG_INTERLACE: u32 = 1 << 4;
+
+/// `struct drm_mode_card_res`
+#[repr(C)]
+#[derive(Default)]
+struct CardResources {
+    fb_id_ptr: u64,
+    crtc_id_ptr: u64,
+    connector_id_ptr: u64,
+    encoder_id_ptr: u64,
+    count_fbs: u32,
+    count_crtcs: u32,
+    count_connectors: u32,
+    count_encoders: u32,
+    min_width: u32,
+    max_width: u32,
+    min_height: u32,
+    max_height: u32,
+}
+
+/// `struct drm_mode_get_connector`
+#[repr(C)]
+#[derive(Default)]
+struct Connector {
+    encoders_ptr: u64,
+    modes_ptr: u64,
+    props_ptr: u64,
+    prop_values_ptr: u64,
+    count_modes: u32,
+    count_props: u32,
+    count_encoders: u32,
+    encoder_id: u32,
+    connector_id: u32,
+    connector_type: u32,
+    connector_type_id: u32,
+    connection: u32,
+    mm_width: u32,
+    mm_height: u32,
+    subpixel: u32,
+    pad: u32,
+}
+
+/// `struct drm_mode_get_encoder`
+#[repr(C)]
+#[derive(Default)]
+struct Encoder {
+    encoder_id: u32,
+    encoder_type: u32,
+    crtc_id: u32,
+    possible_crtcs: u32,
+    possible_clones: u32,
+}
+
+/// `struct drm_mode_modeinfo`
+#[repr(C)]
+#[derive(Default)]
+struct ModeInfo {
+    clock: u32,
+    hdisplay: u16,
+    hsync_start: u16,
+    hsync_end: u16,
+    htotal: u16,
+    hskew: u16,
+    vdisplay: u16,
+    vsync_start: u16,
+    vsync_end: u16,
+    vtotal: u16,
+    vscan: u16,
+    vrefresh: u32,
+    flags: u32,
+    kind: u32,
+    name: [u8; 32],
+}
+
+/// `struct drm_mode_crtc`
+#[repr(C)]
+#[derive(Default)]
+struct Crtc {
+    set_connectors_ptr: u64,
+    count_connectors: u32,
+    crtc_id: u32,
+    fb_id: u32,
+    x: u32,
+    y: u32,
+    gamma_size: u32,
+    mode_valid: u32,
+    mode: ModeInfo,
+}
+
+/// `DRM_IOWR(nr, T)`: read and write, type 'd'
+const fn iowr<T>(nr: u64) -> u64 {
+    (3 << 30) | ((size_of::<T>() as u64) << 16) | ((b'd' as u64) << 8) | nr
+}
+
+const GET_RESOURCES: u64 = iowr::<CardResources>(0xa0);
+const GET_CRTC: u64 = iowr::<Crtc>(0xa1);
+const GET_ENCODER: u64 = iowr::<Encoder>(0xa6);
+const GET_CONNECTOR: u64 = iowr::<Connector>(0xa7);
+
+/// Run a DRM ioctl that fills in `arg`
+fn ioctl<T>(card: &File, request: u64, arg: &mut T) -> Option<()> {
+    let result = unsafe { libc::ioctl(card.as_raw_fd(), request as _, arg as *mut T) };
+    (result == 0).then_some(())
+}
+
+fn connector_ids(card: &File) -> Option<Vec<u32>> {
+    let mut resources = CardResources::default();
+    ioctl(card, GET_RESOURCES, &mut resources)?;
+
+    // the second call fills in as many IDs as the first one counted
+    let mut ids = vec![0u32; resources.count_connectors as usize];
+    let mut resources = CardResources {
+        connector_id_ptr: ids.as_mut_ptr() as u64,
+        count_connectors: ids.len() as u32,
+        ..CardResources::default()
+    };
+    ioctl(card, GET_RESOURCES, &mut resources)?;
+    ids.truncate(resources.count_connectors as usize);
+    Some(ids)
+}
+
+/// Connector name and the mode of the CRTC behind it, if it is lit
+fn active_mode(card: &File, connector_id: u32) -> Option<(String, Mode)> {
+    // the kernel copies the mode list only when it fits, so one slot is never overrun
+    let mut modes = [ModeInfo::default()];
+    let mut connector = Connector {
+        connector_id,
+        modes_ptr: modes.as_mut_ptr() as u64,
+        count_modes: modes.len() as u32,
+        ..Connector::default()
+    };
+    ioctl(card, GET_CONNECTOR, &mut connector)?;
+    let kind = CONNECTOR_TYPES.get(connector.connector_type as usize)?;
+    let name = format!("{}-{}", kind, connector.connector_type_id);
+
+    let mut encoder = Encoder {
+        encoder_id: connector.encoder_id,
+        ..Encoder::default()
+    };
+    if encoder.encoder_id == 0 {
+        return None;
+    }
+    ioctl(card, GET_ENCODER, &mut encoder)?;
+
+    let mut crtc = Crtc {
+        crtc_id: encoder.crtc_id,
+        ..Crtc::default()
+    };
+    if crtc.crtc_id == 0 {
+        return None;
+    }
+    ioctl(card, GET_CRTC, &mut crtc)?;
+    let mode = &crtc.mode;
+    let total = mode.htotal as u64 * mode.vtotal as u64;
+    if crtc.mode_valid == 0 || total == 0 {
+        return None;
+    }
+
+    // like EDID timings, an interlaced mode's rate is the field rate, two per frame
+    let interlaced = mode.flags & DRM_MODE_FLAG_INTERLACE != 0;
+    let frame_rate = mode.clock as f64 * 1000.0 / total as f64;
+    Some((
+        name,
+        Mode {
+            width: mode.hdisplay as u32,
+            height: mode.vdisplay as u32,
+            refresh: if interlaced {
+                frame_rate * 2.0
+            } else {
+                frame_rate
+            },
+            interlaced,
+        },
+    ))
+}
+
+/// Modes in use on `card`, e.g. "card0", by connector name without the card prefix.
+/// Empty when the device cannot be opened, as over SSH without access to the seat
+pub(crate) fn active_modes(card: &str) -> Vec<(String, Mode)> {
+    let Ok(device) = File::open(format!("/dev/dri/{}", card)) else {
+        return Vec::new();
+    };
+    connector_ids(&device)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|id| active_mode(&device, id))
+        .collect()
+}
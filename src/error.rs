@@ -0,0 +1,96 @@
+//! Why a detector came back empty
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::{env, fmt, fs, io};
+
+/// Why a detector could not produce a value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// A file or directory could not be read
+    Io { path: PathBuf, kind: io::ErrorKind },
+    /// A helper program is not installed or exited with an error
+    Command {
+        program: &'static str,
+        reason: String,
+    },
+    /// The source was readable but not in the expected format
+    Parse { source: String, reason: String },
+    /// None of these environment variables is set
+    Env(&'static [&'static str]),
+    /// Every source was readable but none had the value, e.g. no display controller in
+    /// `/sys/bus/pci/devices`
+    NotFound(&'static str),
+    /// A system call or `sys-info` query failed
+    Sys(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io { path, kind } => write!(f, "{}: {}", path.display(), kind),
+            Error::Command { program, reason } => write!(f, "`{}` {}", program, reason),
+            Error::Parse { source, reason } => write!(f, "{}: {}", source, reason),
+            Error::Env(keys) => write!(f, "${} not set", keys.join(", $")),
+            Error::NotFound(what) => write!(f, "no {}", what),
+            Error::Sys(reason) => f.write_str(reason),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    pub(crate) fn parse(source: impl fmt::Display, reason: impl Into<String>) -> Error {
+        Error::Parse {
+            source: source.to_string(),
+            reason: reason.into(),
+        }
+    }
+
+    pub(crate) fn io(path: &Path, err: io::Error) -> Error {
+        Error::Io {
+            path: path.to_path_buf(),
+            kind: err.kind(),
+        }
+    }
+}
+
+pub(crate) fn read(path: impl AsRef<Path>) -> Result<String> {
+    let path = path.as_ref();
+    fs::read_to_string(path).map_err(|err| Error::io(path, err))
+}
+
+pub(crate) fn read_dir(path: impl AsRef<Path>) -> Result<fs::ReadDir> {
+    let path = path.as_ref();
+    fs::read_dir(path).map_err(|err| Error::io(path, err))
+}
+
+/// Stdout of a successful run of `program`
+pub(crate) fn run(program: &'static str, args: &[&str]) -> Result<String> {
+    let output = Command::new(program).args(args).output().map_err(|err| {
+        let reason = match err.kind() {
+            io::ErrorKind::NotFound => "is not installed".to_string(),
+            kind => format!("could not be started: {}", kind),
+        };
+        Error::Command { program, reason }
+    })?;
+
+    if !output.status.success() {
+        return Err(Error::Command {
+            program,
+            reason: format!("failed with {}", output.status),
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Value of the first of `keys` that is set and not empty
+pub(crate) fn env_var(keys: &'static [&'static str]) -> Result<String> {
+    keys.iter()
+        .filter_map(|key| env::var(key).ok())
+        .find(|value| !value.is_empty())
+        .ok_or(Error::Env(keys))
+}
@@ -0,0 +1,184 @@
+//! Graphics cards from the PCI bus in sysfs, named through the system `pci.ids`
+
+use crate::error::{Error, Result, read, read_dir};
+use std::fs;
+use std::path::Path;
+
+const PCI_DEVICES: &str = "/sys/bus/pci/devices";
+const DRM: &str = "/sys/class/drm";
+
+/// Where distros install the pciutils/hwdata database
+const PCI_IDS: &[&str] = &[
+    "/usr/share/hwdata/pci.ids",
+    "/usr/share/misc/pci.ids",
+    "/usr/share/pci.ids",
+    "/usr/share/pciids/pci.ids",
+    "/var/lib/pciutils/pci.ids",
+];
+
+/// PCI base class of display controllers (VGA, XGA, 3D and "other")
+const DISPLAY_CLASS: u32 = 0x03;
+
+/// Short names for vendors whose `pci.ids` names are long, e.g. "Advanced Micro Devices, Inc. [AMD/ATI]"
+const VENDORS: &[(u16, &str)] = &[
+    (0x1002, "AMD"),
+    (0x1022, "AMD"),
+    (0x10de, "NVIDIA"),
+    (0x8086, "Intel"),
+    (0x1a03, "ASPEED"),
+    (0x102b, "Matrox"),
+    (0x1af4, "Virtio"),
+    (0x1234, "QEMU"),
+    (0x15ad, "VMware"),
+    (0x80ee, "VirtualBox"),
+    (0x1414, "Microsoft"),
+    (0x5143, "Qualcomm"),
+    (0x13b5, "ARM"),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gpu {
+    /// Short vendor name, e.g. "NVIDIA"
+    pub vendor: String,
+    /// Marketing name when `pci.ids` has one, e.g. "GeForce GTX 1650 Mobile / Max-Q"
+    pub name: String,
+    pub vendor_id: u16,
+    pub device_id: u16,
+    /// PCI address, e.g. "0000:01:00.0"
+    pub address: String,
+    /// Kernel driver bound to the card, e.g. "amdgpu"
+    pub driver: Option<String>,
+    /// The card a connected monitor is plugged into, or the firmware's boot display
+    pub display: bool,
+}
+
+/// Every display controller on the PCI bus, integrated and discrete, in bus order
+pub fn get_gpus() -> Result<Vec<Gpu>> {
+    let mut devices: Vec<_> = read_dir(PCI_DEVICES)?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            read_hex(path.join("class")).is_some_and(|class| class >> 16 == DISPLAY_CLASS)
+        })
+        .collect();
+    devices.sort();
+
+    if devices.is_empty() {
+        return Err(Error::NotFound("display controller on the PCI bus"));
+    }
+
+    let pci_ids = PCI_IDS.iter().find_map(|path| read(path).ok());
+    let connected = connected_cards();
+
+    let mut gpus: Vec<Gpu> = devices
+        .iter()
+        .map(|device| {
+            let vendor_id = read_hex(device.join("vendor")).unwrap_or(0) as u16;
+            let device_id = read_hex(device.join("device")).unwrap_or(0) as u16;
+            let (vendor_name, device_name) = pci_ids
+                .as_deref()
+                .map(|ids| lookup(ids, vendor_id, device_id))
+                .unwrap_or_default();
+
+            let address = device
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned();
+
+            Gpu {
+                vendor: vendor_short_name(vendor_id, vendor_name),
+                name: device_name
+                    .map(marketing_name)
+                    .unwrap_or_else(|| format!("Device {:04x}", device_id)),
+                vendor_id,
+                device_id,
+                display: connected.contains(&address),
+                address,
+                driver: fs::read_link(device.join("driver"))
+                    .ok()
+                    .and_then(|driver| Some(driver.file_name()?.to_string_lossy().into_owned())),
+            }
+        })
+        .collect();
+
+    // no DRM connectors to go by (e.g. a VM console), so trust the firmware
+    if !gpus.iter().any(|gpu| gpu.display) {
+        for (gpu, device) in gpus.iter_mut().zip(&devices) {
+            gpu.display = read_hex(device.join("boot_vga")) == Some(1);
+        }
+    }
+
+    Ok(gpus)
+}
+
+/// `0x030000` or `1`, as sysfs writes PCI attributes
+fn read_hex(path: impl AsRef<Path>) -> Option<u32> {
+    let contents = fs::read_to_string(path).ok()?;
+    let contents = contents.trim();
+    u32::from_str_radix(contents.strip_prefix("0x").unwrap_or(contents), 16).ok()
+}
+
+/// PCI addresses of cards with at least one connected output, from `/sys/class/drm/card0-HDMI-A-1/status`
+fn connected_cards() -> Vec<String> {
+    let Ok(entries) = fs::read_dir(DRM) else {
+        return Vec::new();
+    };
+
+    entries
+        .flatten()
+        .filter(|connector| {
+            let name = connector.file_name();
+            let name = name.to_string_lossy();
+            name.starts_with("card") && name.contains('-')
+        })
+        .filter(|connector| {
+            fs::read_to_string(connector.path().join("status"))
+                .is_ok_and(|status| status.trim() == "connected")
+        })
+        .filter_map(|connector| {
+            let device = fs::canonicalize(connector.path().join("device")).ok()?;
+            Some(device.file_name()?.to_string_lossy().into_owned())
+        })
+        .collect()
+}
+
+/// Vendor and device names from `pci.ids`, whose entries look like
+/// `10de  NVIDIA Corporation` followed by tab-indented `\t1f99  TU117M [GeForce GTX 1650 Mobile / Max-Q]`
+fn lookup(ids: &str, vendor_id: u16, device_id: u16) -> (Option<&str>, Option<&str>) {
+    let vendor_key = format!("{:04x}  ", vendor_id);
+    let device_key = format!("\t{:04x}  ", device_id);
+
+    let mut lines = ids.lines();
+    let Some(vendor) = lines.find_map(|line| line.strip_prefix(&vendor_key)) else {
+        return (None, None);
+    };
+
+    let device = lines
+        .take_while(|line| line.starts_with('\t') || line.starts_with('#') || line.is_empty())
+        .find_map(|line| line.strip_prefix(&device_key));
+    (Some(vendor), device)
+}
+
+fn vendor_short_name(vendor_id: u16, pci_ids_name: Option<&str>) -> String {
+    if let Some((_, name)) = VENDORS.iter().find(|(id, _)| *id == vendor_id) {
+        return name.to_string();
+    }
+    match pci_ids_name {
+        Some(name) => name
+            .trim_end_matches(" Corporation")
+            .trim_end_matches(", Inc.")
+            .trim_end_matches(" Inc.")
+            .to_string(),
+        None => format!("Vendor {:04x}", vendor_id),
+    }
+}
+
+/// "TU117M [GeForce GTX 1650 Mobile / Max-Q]" becomes "GeForce GTX 1650 Mobile / Max-Q"
+fn marketing_name(name: &str) -> String {
+    name.split_once('[')
+        .and_then(|(_, rest)| rest.rsplit_once(']'))
+        .map(|(inside, _)| inside)
+        .unwrap_or(name)
+        .to_string()
+}
@@ -0,0 +1,123 @@
+//! Which machine this is, from DMI tables on PCs and the device tree on ARM boards
+
+use crate::error::{Error, Result};
+use std::fs;
+use std::path::Path;
+
+const DMI: &str = "/sys/class/dmi/id";
+const DEVICE_TREE_MODEL: &str = "/proc/device-tree/model";
+
+/// What vendors leave in DMI fields they did not bother to fill, compared case-insensitively
+const PLACEHOLDERS: &[&str] = &[
+    "To Be Filled By O.E.M.",
+    "To be filled by OEM",
+    "O.E.M.",
+    "OEM",
+    "Default string",
+    "System manufacturer",
+    "System Product Name",
+    "System Version",
+    "Base Board Product Name",
+    "Not Applicable",
+    "Not Specified",
+    "Not Available",
+    "None",
+    "Undefined",
+    "Unknown",
+    "Type1ProductConfigId",
+    "INVALID",
+    "0123456789",
+    "x.x",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bios {
+    pub version: String,
+    /// As the firmware reports it, usually `MM/DD/YYYY`
+    pub date: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Host {
+    /// e.g. "LENOVO"; `None` on device-tree boards, whose model includes it
+    pub vendor: Option<String>,
+    /// e.g. "20XWCTO1WW" or "Raspberry Pi 4 Model B Rev 1.4"
+    pub product: Option<String>,
+    /// Lenovo keeps the marketing name here, e.g. "ThinkPad X1 Carbon Gen 9"
+    pub version: Option<String>,
+    /// Motherboard vendor and model
+    pub board: Option<String>,
+    pub bios: Option<Bios>,
+}
+
+impl Host {
+    /// e.g. "LENOVO 20XWCTO1WW (ThinkPad X1 Carbon Gen 9)"
+    pub fn model(&self) -> Option<String> {
+        let product = match (&self.vendor, &self.product) {
+            (Some(vendor), Some(product)) if product.starts_with(vendor.as_str()) => {
+                product.clone()
+            }
+            (Some(vendor), Some(product)) => format!("{} {}", vendor, product),
+            (Some(only), None) | (None, Some(only)) => only.clone(),
+            (None, None) => return self.version.clone(),
+        };
+        match &self.version {
+            Some(version) if !product.contains(version.as_str()) => {
+                Some(format!("{} ({})", product, version))
+            }
+            _ => Some(product),
+        }
+    }
+}
+
+fn is_placeholder(value: &str) -> bool {
+    PLACEHOLDERS
+        .iter()
+        .any(|placeholder| placeholder.eq_ignore_ascii_case(value))
+}
+
+/// A DMI field, or `None` for missing, unreadable (some are root-only) and placeholder values
+fn dmi(field: &str) -> Option<String> {
+    let value = fs::read_to_string(Path::new(DMI).join(field)).ok()?;
+    let value = value.trim();
+    (!value.is_empty() && !is_placeholder(value)).then(|| value.to_string())
+}
+
+fn from_dmi() -> Option<Host> {
+    let board = match (dmi("board_vendor"), dmi("board_name")) {
+        (Some(vendor), Some(name)) => Some(format!("{} {}", vendor, name)),
+        (vendor, name) => name.or(vendor),
+    };
+    let bios = dmi("bios_version").map(|version| Bios {
+        version,
+        date: dmi("bios_date"),
+    });
+    let host = Host {
+        vendor: dmi("sys_vendor"),
+        product: dmi("product_name"),
+        version: dmi("product_version"),
+        board,
+        bios,
+    };
+    (host.model().is_some() || host.board.is_some()).then_some(host)
+}
+
+/// The model string is NUL-terminated, as every device-tree property
+fn from_device_tree() -> Option<Host> {
+    let model = fs::read(DEVICE_TREE_MODEL).ok()?;
+    let model = String::from_utf8_lossy(&model);
+    let model = model.trim_end_matches('\0').trim();
+    (!model.is_empty()).then(|| Host {
+        vendor: None,
+        product: Some(model.to_string()),
+        version: None,
+        board: None,
+        bios: None,
+    })
+}
+
+pub fn get_host() -> Result<Host> {
+    from_dmi().or_else(from_device_tree).ok_or(Error::NotFound(
+        "machine model in /sys/class/dmi/id or /proc/device-tree",
+    ))
+}
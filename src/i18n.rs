@@ -0,0 +1,308 @@
+//! Message catalogs for everything fortfetch prints
+
+use std::env;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lang {
+    /// The original Fortuna Telecom® wording
+    RuJoke,
+    Ru,
+    En,
+}
+
+impl Lang {
+    pub const CODES: [&'static str; 3] = ["ru-joke", "ru", "en"];
+
+    pub fn from_code(code: &str) -> Option<Lang> {
+        match code {
+            "ru-joke" | "fortuna" => Some(Lang::RuJoke),
+            "ru" => Some(Lang::Ru),
+            "en" => Some(Lang::En),
+            _ => None,
+        }
+    }
+
+    /// Pick a catalog from `LC_ALL`, `LC_MESSAGES` or `LANG`, in that order.
+    /// Russian and unset/C locales keep the original wording, anything else gets English
+    pub fn from_env() -> Lang {
+        let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|key| env::var(key).ok())
+            .find(|value| !value.is_empty());
+
+        match locale.as_deref() {
+            None | Some("C") | Some("POSIX") => Lang::RuJoke,
+            Some(locale) if locale.starts_with("C.") => Lang::RuJoke,
+            Some(locale) if locale.starts_with("ru") => Lang::RuJoke,
+            Some(_) => Lang::En,
+        }
+    }
+
+    pub fn messages(self) -> &'static Messages {
+        match self {
+            Lang::RuJoke => &RU_JOKE,
+            Lang::Ru => &RU,
+            Lang::En => &EN,
+        }
+    }
+}
+
+pub struct Messages {
+    pub system: &'static str,
+    pub hardware: &'static str,
+    pub performance: &'static str,
+    pub environment: &'static str,
+    pub power: &'static str,
+
+    pub username: &'static str,
+    pub hostname: &'static str,
+    pub distro: &'static str,
+    pub kernel: &'static str,
+    pub uptime: &'static str,
+    pub packages: &'static str,
+    pub shell: &'static str,
+    pub host: &'static str,
+    pub bios: &'static str,
+    pub board: &'static str,
+    pub cpu: &'static str,
+    pub cpu_cache: &'static str,
+    pub gpu: &'static str,
+    pub memory: &'static str,
+    pub swap: &'static str,
+    pub disk: &'static str,
+    pub cpu_usage: &'static str,
+    pub temperature: &'static str,
+    pub load: &'static str,
+    pub de: &'static str,
+    pub wm: &'static str,
+    pub resolution: &'static str,
+    pub terminal: &'static str,
+    pub locale: &'static str,
+    pub processes: &'static str,
+    pub users: &'static str,
+    pub network: &'static str,
+    pub battery: &'static str,
+
+    /// `{d}`, `{h}` and `{m}` are replaced with days, hours and minutes
+    pub uptime_format: &'static str,
+    pub no_connection: &'static str,
+    /// Marks the interface holding the default route
+    pub default_route: &'static str,
+    /// `{n}` is replaced with the link speed in Mbit/s
+    pub link_speed: &'static str,
+    /// After a resolution that is the monitor's preferred one, not necessarily the one in use
+    pub native_mode: &'static str,
+    /// Swap line when there is no swap at all
+    pub swap_disabled: &'static str,
+    /// Marks the GPU driving the display when there are several
+    pub gpu_display: &'static str,
+    pub charging: &'static str,
+    pub discharging: &'static str,
+    pub full: &'static str,
+    pub not_charging: &'static str,
+    pub unknown_status: &'static str,
+    /// Label of the charger line
+    pub ac_adapter: &'static str,
+    pub ac_online: &'static str,
+    pub ac_offline: &'static str,
+    /// `{h}` and `{m}` are replaced with hours and minutes
+    pub time_to_empty: &'static str,
+    pub time_to_full: &'static str,
+    /// `{p}` is replaced with the percent of the design capacity left
+    pub battery_health: &'static str,
+    /// `{n}` is replaced with the charge cycle count
+    pub battery_cycles: &'static str,
+    /// `{t}` is replaced with the threshold, e.g. "80°C"
+    pub temperature_high: &'static str,
+    pub temperature_critical: &'static str,
+    pub colors: &'static str,
+}
+
+impl Messages {
+    pub fn format_uptime(&self, days: u64, hours: u64, minutes: u64) -> String {
+        self.uptime_format
+            .replace("{d}", &days.to_string())
+            .replace("{h}", &hours.to_string())
+            .replace("{m}", &minutes.to_string())
+    }
+}
+
+static RU_JOKE: Messages = Messages {
+    system: "Система",
+    hardware: "Железо",
+    performance: "Производительность",
+    environment: "Окружение",
+    power: "Питание",
+
+    username: "Логин",
+    hostname: "Абонент",
+    distro: "Тариф",
+    kernel: "Прошивка",
+    uptime: "Пополнение",
+    packages: "Вирусов",
+    shell: "Оболочка",
+    host: "Тачка",
+    bios: "Прошивка тачки",
+    board: "Материнка",
+    cpu: "ЦП",
+    cpu_cache: "Заначка ЦП",
+    gpu: "ГПУ",
+    memory: "Оперативка",
+    swap: "Подкачка",
+    disk: "Дискета",
+    cpu_usage: "Загрузка ЦП",
+    temperature: "Температура",
+    load: "Нагрузка",
+    de: "Админка",
+    wm: "Оконщик",
+    resolution: "Экран",
+    terminal: "Терминал",
+    locale: "Локаль",
+    processes: "Процессов",
+    users: "Юзеров",
+    network: "Сеть",
+    battery: "Батарея",
+
+    uptime_format: "{d}д. {h}ч. {m}м. назад",
+    no_connection: "Нет соединения",
+    default_route: "основной канал",
+    link_speed: "{n} Мбит/с",
+    native_mode: "родное",
+    swap_disabled: "Не подключена",
+    gpu_display: "на экране",
+    charging: "Заряжается",
+    discharging: "Разряжается",
+    full: "Заряжена",
+    not_charging: "Не заряжается",
+    unknown_status: "Неизвестно",
+    ac_adapter: "Зарядка",
+    ac_online: "В розетке",
+    ac_offline: "На батарее",
+    time_to_empty: "{h}ч. {m}м. до отключения",
+    time_to_full: "{h}ч. {m}м. до пополнения",
+    battery_health: "здоровье {p}%",
+    battery_cycles: "{n} циклов",
+    temperature_high: "перегрев {t}",
+    temperature_critical: "пожар {t}",
+    colors: "Цвета:",
+};
+
+static RU: Messages = Messages {
+    system: "Система",
+    hardware: "Оборудование",
+    performance: "Производительность",
+    environment: "Окружение",
+    power: "Питание",
+
+    username: "Пользователь",
+    hostname: "Хост",
+    distro: "Дистрибутив",
+    kernel: "Ядро",
+    uptime: "Время работы",
+    packages: "Пакеты",
+    shell: "Оболочка",
+    host: "Модель",
+    bios: "BIOS",
+    board: "Материнская плата",
+    cpu: "Процессор",
+    cpu_cache: "Кэш ЦП",
+    gpu: "Видеокарта",
+    memory: "Память",
+    swap: "Подкачка",
+    disk: "Диск",
+    cpu_usage: "Загрузка ЦП",
+    temperature: "Температура",
+    load: "Средняя нагрузка",
+    de: "Рабочий стол",
+    wm: "Оконный менеджер",
+    resolution: "Разрешение",
+    terminal: "Терминал",
+    locale: "Локаль",
+    processes: "Процессы",
+    users: "Пользователи",
+    network: "Сеть",
+    battery: "Батарея",
+
+    uptime_format: "{d} д. {h} ч. {m} мин.",
+    no_connection: "Нет соединения",
+    default_route: "основной",
+    link_speed: "{n} Мбит/с",
+    native_mode: "родное",
+    swap_disabled: "Отключена",
+    gpu_display: "основная",
+    charging: "Заряжается",
+    discharging: "Разряжается",
+    full: "Заряжена",
+    not_charging: "Не заряжается",
+    unknown_status: "Неизвестно",
+    ac_adapter: "Адаптер питания",
+    ac_online: "Подключён",
+    ac_offline: "Отключён",
+    time_to_empty: "{h} ч {m} мин до разряда",
+    time_to_full: "{h} ч {m} мин до зарядки",
+    battery_health: "износ: осталось {p}%",
+    battery_cycles: "циклов: {n}",
+    temperature_high: "макс. {t}",
+    temperature_critical: "крит. {t}",
+    colors: "Цвета:",
+};
+
+static EN: Messages = Messages {
+    system: "System",
+    hardware: "Hardware",
+    performance: "Performance",
+    environment: "Environment",
+    power: "Power",
+
+    username: "User",
+    hostname: "Host",
+    distro: "Distro",
+    kernel: "Kernel",
+    uptime: "Uptime",
+    packages: "Packages",
+    shell: "Shell",
+    host: "Model",
+    bios: "BIOS",
+    board: "Board",
+    cpu: "CPU",
+    cpu_cache: "CPU cache",
+    gpu: "GPU",
+    memory: "Memory",
+    swap: "Swap",
+    disk: "Disk",
+    cpu_usage: "CPU usage",
+    temperature: "Temperature",
+    load: "Load average",
+    de: "Desktop",
+    wm: "WM",
+    resolution: "Resolution",
+    terminal: "Terminal",
+    locale: "Locale",
+    processes: "Processes",
+    users: "Users",
+    network: "Network",
+    battery: "Battery",
+
+    uptime_format: "{d}d {h}h {m}m",
+    no_connection: "Disconnected",
+    default_route: "default",
+    link_speed: "{n} Mbit/s",
+    native_mode: "native",
+    swap_disabled: "Disabled",
+    gpu_display: "display",
+    charging: "Charging",
+    discharging: "Discharging",
+    full: "Full",
+    not_charging: "Not charging",
+    unknown_status: "Unknown",
+    ac_adapter: "AC adapter",
+    ac_online: "Online",
+    ac_offline: "Offline",
+    time_to_empty: "{h}h {m}m left",
+    time_to_full: "{h}h {m}m to full",
+    battery_health: "health {p}%",
+    battery_cycles: "{n} cycles",
+    temperature_high: "high {t}",
+    temperature_critical: "crit {t}",
+    colors: "Colors:",
+};
@@ -0,0 +1,496 @@
+use fortfetch::{
+    Address, Battery, BatteryStatus, CacheKind, Cpu, Disk, Display, Distro, Gpu, Interface,
+    LinkState, Power, Sensor, SessionType, SystemInfo, Terminal, Timing, Usage,
+};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+#[derive(Serialize)]
+struct UsageReport {
+    used_bytes: u64,
+    total_bytes: u64,
+    percent: u64,
+}
+
+impl From<&Usage> for UsageReport {
+    fn from(usage: &Usage) -> Self {
+        UsageReport {
+            used_bytes: usage.used,
+            total_bytes: usage.total,
+            percent: usage.percent(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct MemoryReport {
+    #[serde(flatten)]
+    usage: UsageReport,
+    available_bytes: u64,
+    buffers_bytes: u64,
+    cached_bytes: u64,
+    shared_bytes: u64,
+}
+
+#[derive(Serialize)]
+struct SwapReport {
+    #[serde(flatten)]
+    usage: UsageReport,
+    zram: Option<ZramReport>,
+}
+
+#[derive(Serialize)]
+struct DiskReport {
+    mountpoint: String,
+    device: String,
+    fstype: String,
+    #[serde(flatten)]
+    usage: UsageReport,
+}
+
+impl From<&Disk> for DiskReport {
+    fn from(disk: &Disk) -> Self {
+        DiskReport {
+            mountpoint: disk.mountpoint.clone(),
+            device: disk.device.clone(),
+            fstype: disk.fstype.clone(),
+            usage: UsageReport::from(&disk.usage),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct SensorReport {
+    kind: &'static str,
+    chip: String,
+    label: Option<String>,
+    device: Option<String>,
+    celsius: f64,
+    high_celsius: Option<f64>,
+    critical_celsius: Option<f64>,
+}
+
+impl From<&Sensor> for SensorReport {
+    fn from(sensor: &Sensor) -> Self {
+        SensorReport {
+            kind: sensor.kind.name(),
+            chip: sensor.chip.clone(),
+            label: sensor.label.clone(),
+            device: sensor.device.clone(),
+            celsius: sensor.celsius,
+            high_celsius: sensor.high,
+            critical_celsius: sensor.critical,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ZramReport {
+    size_bytes: u64,
+    stored_bytes: u64,
+    compressed_bytes: u64,
+}
+
+#[derive(Serialize)]
+struct PackageReport {
+    manager: &'static str,
+    count: usize,
+}
+
+#[derive(Serialize)]
+struct HostReport {
+    vendor: Option<String>,
+    product: Option<String>,
+    version: Option<String>,
+    board: Option<String>,
+    bios_version: Option<String>,
+    bios_date: Option<String>,
+}
+
+#[derive(Serialize)]
+struct CpuReport {
+    model: String,
+    sockets: usize,
+    cores: usize,
+    threads: usize,
+    max_frequency_mhz: Option<u32>,
+    current_frequency_mhz: Option<u32>,
+    caches: Vec<CacheReport>,
+}
+
+#[derive(Serialize)]
+struct CacheReport {
+    level: u8,
+    #[serde(rename = "type")]
+    kind: &'static str,
+    size_bytes: u64,
+    count: usize,
+}
+
+impl From<&Cpu> for CpuReport {
+    fn from(cpu: &Cpu) -> Self {
+        CpuReport {
+            model: cpu.model.clone(),
+            sockets: cpu.sockets,
+            cores: cpu.cores,
+            threads: cpu.threads,
+            max_frequency_mhz: cpu.max_frequency,
+            current_frequency_mhz: cpu.current_frequency,
+            caches: cpu
+                .caches
+                .iter()
+                .map(|cache| CacheReport {
+                    level: cache.level,
+                    kind: match cache.kind {
+                        CacheKind::Data => "data",
+                        CacheKind::Instruction => "instruction",
+                        CacheKind::Unified => "unified",
+                    },
+                    size_bytes: cache.size,
+                    count: cache.count,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct CpuUsageReport {
+    percent: f64,
+    per_core: Vec<f64>,
+}
+
+#[derive(Serialize)]
+struct GpuReport {
+    vendor: String,
+    name: String,
+    /// "10de:1f99"
+    pci_id: String,
+    address: String,
+    driver: Option<String>,
+    display: bool,
+}
+
+impl From<&Gpu> for GpuReport {
+    fn from(gpu: &Gpu) -> Self {
+        GpuReport {
+            vendor: gpu.vendor.clone(),
+            name: gpu.name.clone(),
+            pci_id: format!("{:04x}:{:04x}", gpu.vendor_id, gpu.device_id),
+            address: gpu.address.clone(),
+            driver: gpu.driver.clone(),
+            display: gpu.display,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct PowerReport {
+    ac_online: Option<bool>,
+    batteries: Vec<BatteryReport>,
+}
+
+impl From<&Power> for PowerReport {
+    fn from(power: &Power) -> Self {
+        PowerReport {
+            ac_online: power.ac_online,
+            batteries: power.batteries.iter().map(BatteryReport::from).collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct BatteryReport {
+    name: String,
+    model: Option<String>,
+    peripheral: bool,
+    capacity_percent: u8,
+    status: &'static str,
+    time_to_empty_seconds: Option<u64>,
+    time_to_full_seconds: Option<u64>,
+    health_percent: Option<f64>,
+    cycle_count: Option<u32>,
+}
+
+impl From<&Battery> for BatteryReport {
+    fn from(battery: &Battery) -> Self {
+        let status = match battery.status {
+            BatteryStatus::Charging => "charging",
+            BatteryStatus::Discharging => "discharging",
+            BatteryStatus::Full => "full",
+            BatteryStatus::NotCharging => "not_charging",
+            BatteryStatus::Unknown => "unknown",
+        };
+
+        BatteryReport {
+            name: battery.name.clone(),
+            model: battery.model.clone(),
+            peripheral: battery.peripheral,
+            capacity_percent: battery.capacity,
+            status,
+            time_to_empty_seconds: battery.time_to_empty.map(|time| time.as_secs()),
+            time_to_full_seconds: battery.time_to_full.map(|time| time.as_secs()),
+            health_percent: battery.health,
+            cycle_count: battery.cycle_count,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct DistroReport {
+    name: String,
+    id: Option<String>,
+    id_like: Vec<String>,
+}
+
+impl From<&Distro> for DistroReport {
+    fn from(distro: &Distro) -> Self {
+        DistroReport {
+            name: distro.name.clone(),
+            id: distro.id.clone(),
+            id_like: distro.id_like.clone(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct DisplayReport {
+    connector: String,
+    name: Option<String>,
+    manufacturer: Option<String>,
+    /// PNP ID and product code, e.g. "DEL:a0a4"
+    model_id: Option<String>,
+    serial: Option<String>,
+    year: Option<u16>,
+    width_mm: Option<u32>,
+    height_mm: Option<u32>,
+    diagonal_inches: Option<f64>,
+    width: Option<u32>,
+    height: Option<u32>,
+    refresh_hz: Option<f64>,
+    /// The mode in use rather than the monitor's native one
+    current: bool,
+}
+
+impl From<&Display> for DisplayReport {
+    fn from(display: &Display) -> Self {
+        let edid = display.edid.as_ref();
+        DisplayReport {
+            connector: display.connector.clone(),
+            name: edid.and_then(|edid| edid.name.clone()),
+            manufacturer: edid.map(|edid| edid.manufacturer().to_string()),
+            model_id: edid
+                .map(|edid| format!("{}:{:04x}", edid.manufacturer_id, edid.product_code)),
+            serial: edid.and_then(|edid| edid.serial.clone()),
+            year: edid.map(|edid| edid.year),
+            width_mm: edid.and_then(|edid| edid.width_mm),
+            height_mm: edid.and_then(|edid| edid.height_mm),
+            diagonal_inches: display.diagonal(),
+            width: display.width,
+            height: display.height,
+            refresh_hz: display.refresh,
+            current: display.current,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct InterfaceReport {
+    name: String,
+    state: &'static str,
+    mac: Option<String>,
+    ipv4: Vec<String>,
+    ipv6: Vec<String>,
+    speed_mbps: Option<u32>,
+    default_route: bool,
+}
+
+impl From<&Interface> for InterfaceReport {
+    fn from(interface: &Interface) -> Self {
+        let state = match interface.state {
+            LinkState::Up => "up",
+            LinkState::Down => "down",
+            LinkState::Dormant => "dormant",
+            LinkState::Unknown => "unknown",
+        };
+        let strings = |addresses: &[Address]| addresses.iter().map(Address::to_string).collect();
+
+        InterfaceReport {
+            name: interface.name.clone(),
+            state,
+            mac: interface.mac.clone(),
+            ipv4: strings(&interface.ipv4),
+            ipv6: strings(&interface.ipv6),
+            speed_mbps: interface.speed,
+            default_route: interface.default_route,
+        }
+    }
+}
+
+/// A shell or desktop with the version it reported
+#[derive(Serialize)]
+struct VersionedReport {
+    name: String,
+    version: Option<String>,
+}
+
+#[derive(Serialize)]
+struct WindowManagerReport {
+    name: Option<String>,
+    session: Option<&'static str>,
+}
+
+#[derive(Serialize)]
+struct TerminalReport {
+    name: String,
+    kind: &'static str,
+    multiplexer: Option<String>,
+    term: Option<String>,
+}
+
+impl From<&Terminal> for TerminalReport {
+    fn from(terminal: &Terminal) -> Self {
+        TerminalReport {
+            name: terminal.name.clone(),
+            kind: terminal.kind.name(),
+            multiplexer: terminal.multiplexer.clone(),
+            term: terminal.term.clone(),
+        }
+    }
+}
+
+/// Machine-readable counterpart of `get_info()`, one field per detector
+#[derive(Serialize)]
+pub struct Report {
+    username: Option<String>,
+    hostname: Option<String>,
+    distro: Option<DistroReport>,
+    kernel: Option<String>,
+    uptime_seconds: Option<u64>,
+    packages: Option<Vec<PackageReport>>,
+    shell: Option<VersionedReport>,
+    host: Option<HostReport>,
+    cpu: Option<CpuReport>,
+    gpus: Option<Vec<GpuReport>>,
+    memory: Option<MemoryReport>,
+    swap: Option<SwapReport>,
+    disks: Option<Vec<DiskReport>>,
+    cpu_usage: Option<CpuUsageReport>,
+    temperatures: Option<Vec<SensorReport>>,
+    load_average: Option<[f64; 3]>,
+    desktop_environment: Option<VersionedReport>,
+    window_manager: Option<WindowManagerReport>,
+    displays: Option<Vec<DisplayReport>>,
+    terminal: Option<TerminalReport>,
+    locale: Option<String>,
+    processes: Option<usize>,
+    users: Option<usize>,
+    network: Option<Vec<InterfaceReport>>,
+    power: Option<PowerReport>,
+    /// `--debug`: why each failed detector left its field `null`
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    errors: BTreeMap<&'static str, String>,
+}
+
+impl From<&SystemInfo> for Report {
+    fn from(info: &SystemInfo) -> Self {
+        Report {
+            username: info.username.clone(),
+            hostname: info.hostname.clone(),
+            distro: info.distro.as_ref().map(DistroReport::from),
+            kernel: info.kernel.clone(),
+            uptime_seconds: info.uptime.map(|uptime| uptime.as_secs()),
+            packages: info.packages.as_ref().map(|counts| {
+                counts
+                    .iter()
+                    .map(|package| PackageReport {
+                        manager: package.manager,
+                        count: package.count,
+                    })
+                    .collect()
+            }),
+            shell: info.shell.as_ref().map(|shell| VersionedReport {
+                name: shell.name.clone(),
+                version: shell.version.clone(),
+            }),
+            host: info.host.as_ref().map(|host| HostReport {
+                vendor: host.vendor.clone(),
+                product: host.product.clone(),
+                version: host.version.clone(),
+                board: host.board.clone(),
+                bios_version: host.bios.as_ref().map(|bios| bios.version.clone()),
+                bios_date: host.bios.as_ref().and_then(|bios| bios.date.clone()),
+            }),
+            cpu: info.cpu.as_ref().map(CpuReport::from),
+            gpus: info
+                .gpus
+                .as_ref()
+                .map(|gpus| gpus.iter().map(GpuReport::from).collect()),
+            memory: info.memory.as_ref().map(|memory| MemoryReport {
+                usage: UsageReport::from(&memory.usage()),
+                available_bytes: memory.available,
+                buffers_bytes: memory.buffers,
+                cached_bytes: memory.cached,
+                shared_bytes: memory.shmem,
+            }),
+            swap: info.swap.as_ref().map(|swap| SwapReport {
+                usage: UsageReport::from(&swap.usage()),
+                zram: swap.zram.map(|zram| ZramReport {
+                    size_bytes: zram.size,
+                    stored_bytes: zram.stored,
+                    compressed_bytes: zram.compressed,
+                }),
+            }),
+            disks: info
+                .disks
+                .as_ref()
+                .map(|disks| disks.iter().map(DiskReport::from).collect()),
+            cpu_usage: info.cpu_usage.as_ref().map(|usage| CpuUsageReport {
+                percent: usage.percent(),
+                per_core: usage.core_percents(),
+            }),
+            temperatures: info
+                .temperatures
+                .as_ref()
+                .map(|sensors| sensors.iter().map(SensorReport::from).collect()),
+            load_average: info.load_average,
+            desktop_environment: info
+                .desktop_environment
+                .as_ref()
+                .map(|desktop| VersionedReport {
+                    name: desktop.name.clone(),
+                    version: desktop.version.clone(),
+                }),
+            window_manager: info.window_manager.as_ref().map(|wm| WindowManagerReport {
+                name: wm.name.clone(),
+                session: wm.session.map(SessionType::name),
+            }),
+            displays: info
+                .displays
+                .as_ref()
+                .map(|displays| displays.iter().map(DisplayReport::from).collect()),
+            terminal: info.terminal.as_ref().map(TerminalReport::from),
+            locale: info.locale.clone(),
+            processes: info.processes,
+            users: info.users,
+            network: info
+                .network
+                .as_ref()
+                .map(|interfaces| interfaces.iter().map(InterfaceReport::from).collect()),
+            power: info.power.as_ref().map(PowerReport::from),
+            errors: BTreeMap::new(),
+        }
+    }
+}
+
+impl Report {
+    pub fn add_errors(&mut self, timings: &[Timing]) {
+        for timing in timings.iter().filter(|timing| timing.failed()) {
+            self.errors.insert(timing.name, timing.status.to_string());
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("report is always serializable")
+    }
+}
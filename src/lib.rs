@@ -1,293 +1,520 @@
-use std::{env, fs};
-use std::process::Command;
-
-pub struct Uptime {
-    days: u64,
-    hours: u64,
-    minutes: u64,
+use std::ffi::CStr;
+use std::path::Path;
+use std::time::Duration;
+
+mod collect;
+mod cpu;
+mod cpu_usage;
+mod desktop;
+mod disk;
+mod display;
+mod drm;
+mod error;
+mod gpu;
+mod host;
+pub mod i18n;
+mod memory;
+pub mod module;
+mod network;
+mod packages;
+mod power;
+mod process;
+mod sensors;
+mod shell;
+mod terminal;
+mod version;
+mod wm;
+
+use collect::{Deadline, spawn};
+pub use collect::{Status, Timing};
+pub use cpu::{Cache, CacheKind, Cpu, get_cpu};
+pub use cpu_usage::{
+    CpuTimes, CpuUsage, CpuUsageSettings, DEFAULT_CPU_USAGE_INTERVAL, get_cpu_usage, read_cpu_times,
+};
+pub use desktop::{DesktopEnvironment, get_desktop_environment};
+pub use disk::{DEFAULT_EXCLUDED_FSTYPES, Disk, DiskSettings, Filter, get_disks};
+pub use display::{Display, Edid, Mode, get_displays, parse_edid};
+pub use error::{Error, Result};
+use error::{env_var, read, read_dir, run};
+pub use gpu::{Gpu, get_gpus};
+pub use host::{Bios, Host, get_host};
+pub use memory::{Memory, Swap, Zram, get_memory, get_swap};
+use module::Module;
+pub use network::{Address, Interface, LinkState, get_interfaces};
+pub use packages::{PackageCount, count_rpmdb, get_package_count};
+pub use power::{Battery, BatteryStatus, Power, get_power};
+pub use sensors::{DEFAULT_SENSORS, Sensor, SensorKind, SensorSettings, get_sensors};
+pub use shell::{Shell, get_shell};
+use std::sync::Arc;
+pub use terminal::{Terminal, TerminalKind, get_terminal};
+pub use wm::{SessionType, WindowManager, get_window_manager};
+
+/// Per-detector time limit used by [`SystemInfo::collect`]
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// Knobs for detectors and formatting; the binary fills them from its config file
+#[derive(Debug, Clone)]
+pub struct Settings {
+    /// How long each detector may run before its field is left `None`
+    pub timeout: Duration,
+    pub cpu_usage: CpuUsageSettings,
+    pub disk: DiskSettings,
+    pub temperature: SensorSettings,
+    /// Units for memory, swap and disk sizes
+    pub units: Units,
+    /// Digits after the decimal point in sizes
+    pub precision: usize,
 }
 
-impl Uptime {
-    pub fn new() -> Option<Uptime> {
+/// How sizes are shown: powers of 1024 ("GiB") or of 1000 ("GB")
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Units {
+    Binary,
+    Decimal,
+}
 
-        let uptime_secs = Self::uptime()?;
+impl Units {
+    pub const NAMES: [&'static str; 2] = ["binary", "decimal"];
 
-        Some(Uptime {
-            days: uptime_secs / 86400,
-            hours: (uptime_secs % 86400) / 3600,
-            minutes: (uptime_secs % 3600) / 60,
-        })
+    pub fn from_name(name: &str) -> Option<Units> {
+        match name {
+            "binary" => Some(Units::Binary),
+            "decimal" => Some(Units::Decimal),
+            _ => None,
+        }
     }
+}
 
-    fn uptime() -> Option<u64> {
-        let contents = fs::read_to_string("/proc/uptime").ok()?;
-        // первые числа секунды uptime
-        let first_part = contents.split_whitespace().next()?;
-        let seconds_float: f64 = first_part.parse().ok()?;
-        Some(seconds_float as u64)
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            timeout: DEFAULT_TIMEOUT,
+            cpu_usage: CpuUsageSettings::default(),
+            disk: DiskSettings::default(),
+            temperature: SensorSettings::default(),
+            units: Units::Binary,
+            precision: 2,
+        }
     }
+}
 
-    pub fn get(&self) -> String {
-        format!("{}д. {}ч. {}м.", self.days, self.hours, self.minutes)
-    }
+/// Everything fortfetch knows about the machine, `None` where a detector found nothing
+#[derive(Debug, Clone, Default)]
+pub struct SystemInfo {
+    pub username: Option<String>,
+    pub hostname: Option<String>,
+    pub distro: Option<Distro>,
+    pub kernel: Option<String>,
+    pub uptime: Option<Duration>,
+    /// One entry per package manager with anything installed
+    pub packages: Option<Vec<PackageCount>>,
+    pub shell: Option<Shell>,
+    /// Machine model, motherboard and firmware
+    pub host: Option<Host>,
+    pub cpu: Option<Cpu>,
+    /// Every display controller, integrated and discrete
+    pub gpus: Option<Vec<Gpu>>,
+    pub memory: Option<Memory>,
+    pub swap: Option<Swap>,
+    pub disks: Option<Vec<Disk>>,
+    /// Load over [`CpuUsageSettings::interval`]
+    pub cpu_usage: Option<CpuUsage>,
+    /// Degrees Celsius
+    pub temperatures: Option<Vec<Sensor>>,
+    pub load_average: Option<[f64; 3]>,
+    pub desktop_environment: Option<DesktopEnvironment>,
+    pub window_manager: Option<WindowManager>,
+    pub displays: Option<Vec<Display>>,
+    pub terminal: Option<Terminal>,
+    pub locale: Option<String>,
+    pub processes: Option<usize>,
+    pub users: Option<usize>,
+    pub network: Option<Vec<Interface>>,
+    pub power: Option<Power>,
 }
 
-pub fn get_package_count() -> Option<usize> {
-    // Debian/Ubuntu (dpkg)
-    if let Ok(contents) = fs::read_to_string("/var/lib/dpkg/status") {
-        let count = contents.lines().filter(|line| line.starts_with("Package:")).count();
-        return Some(count);
+impl SystemInfo {
+    /// Run every detector, giving up on those that take longer than [`DEFAULT_TIMEOUT`]
+    pub fn collect() -> SystemInfo {
+        Self::collect_with(&Settings::default()).0
     }
 
-    // RPM-based (Fedora, RHEL, etc.)
-    if fs::metadata("/var/lib/rpm").is_ok() {
-        let count = fs::read_dir("/var/lib/rpm")
-            .ok()?
-            .filter_map(Result::ok)
-            .filter(|entry| {
-                entry
-                    .path()
-                    .extension()
-                    .map_or(false, |ext| ext == "rpm")
-            })
-            .count();
-        return Some(count);
+    /// Run every detector concurrently; a detector still running after `timeout` is left as `None`
+    pub fn collect_with_timeout(timeout: Duration) -> (SystemInfo, Vec<Timing>) {
+        Self::collect_with(&Settings {
+            timeout,
+            ..Settings::default()
+        })
     }
 
-    // Arch Linux (pacman)
-    if let Ok(entries) = fs::read_dir("/var/lib/pacman/local") {
-        let count = entries
-            .filter_map(Result::ok)
-            .filter(|entry| entry.path().is_dir())
-            .count();
-        return Some(count);
+    /// Run every detector concurrently with the given settings
+    pub fn collect_with(settings: &Settings) -> (SystemInfo, Vec<Timing>) {
+        Self::collect_modules(module::all(), settings)
     }
 
-    None
-}
-
-pub fn get_cpu_model() -> Option<String> {
-    let contents = fs::read_to_string("/proc/cpuinfo").ok()?;
-
-    for line in contents.lines() {
-        if line.starts_with("model name") {
-            return line.split(':').nth(1).map(|s| s.trim().to_string());
+    /// Run only `modules` concurrently, leaving every other field `None`.
+    /// Modules with the same [`Module::source`] share a single run, timed under that name
+    pub fn collect_modules(
+        modules: &[&'static dyn Module],
+        settings: &Settings,
+    ) -> (SystemInfo, Vec<Timing>) {
+        let mut sources: Vec<&'static dyn Module> = Vec::new();
+        for &module in modules {
+            if !sources
+                .iter()
+                .any(|other| other.source() == module.source())
+            {
+                sources.push(module);
+            }
         }
-    }
 
-    None
-}
+        let mut deadline = Deadline::after(settings.timeout);
+        let shared = Arc::new(settings.clone());
+        let pending: Vec<_> = sources
+            .into_iter()
+            .map(|module| {
+                let settings = Arc::clone(&shared);
+                spawn(module.source(), move || module.collect(&settings))
+            })
+            .collect();
 
-pub fn get_gpu_model() -> Option<String> {
-    if Command::new("which")
-        .arg("lspci")
-        .output()
-        .map(|o| o.status.success())
-        .unwrap_or(false)
-    {
-        if let Ok(output) = Command::new("lspci").output() {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-
-            if let Some(line) = stdout
-                .lines()
-                .find(|line| line.to_lowercase().contains("vga") || line.to_lowercase().contains("3d") || line.to_lowercase().contains("2d"))
-            {
-                if let Some(info) = line.splitn(3, ':').nth(2) {
-                    return Some(info.trim().to_string());
-                }
+        let mut info = SystemInfo::default();
+        for update in pending {
+            if let Some(update) = deadline.wait(update) {
+                update(&mut info);
             }
         }
+
+        (info, deadline.into_timings())
     }
-    None
 }
 
-pub fn get_memory_info() -> Option<String> {
-    let mem = sys_info::mem_info().ok()?;
-
-    let used_kb = mem.total.saturating_sub(mem.avail);
-    let total_gb = mem.total as f64 / 1024.0 / 1024.0;
-    let used_gb = used_kb as f64 / 1024.0 / 1024.0;
-    let percentage = (used_kb * 100) / mem.total;
+/// Used and total amount, in bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Usage {
+    pub used: u64,
+    pub total: u64,
+}
 
-    Some(format!("{:.2}GB / {:.2}GB ({}%)", used_gb, total_gb, percentage))
+impl Usage {
+    pub fn percent(&self) -> u64 {
+        (self.used * 100).checked_div(self.total).unwrap_or(0)
+    }
 }
 
-pub fn get_disk_info() -> Option<String> {
-    let disk = sys_info::disk_info().ok()?;
+#[derive(Debug, Clone)]
+pub struct Distro {
+    /// `PRETTY_NAME`, falling back to `NAME`
+    pub name: String,
+    /// `ID`, e.g. "ubuntu"
+    pub id: Option<String>,
+    /// `ID_LIKE`, e.g. ["debian"]
+    pub id_like: Vec<String>,
+}
 
-    let used_mb = disk.total.saturating_sub(disk.free);
-    let total_gb = disk.total as f64 / 1024.0;
-    let used_gb = used_mb as f64 / 1024.0;
-    let percentage = (used_mb * 100) / disk.total;
+impl Distro {
+    /// `ID` followed by `ID_LIKE`, most specific first
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.id.iter().chain(&self.id_like).map(String::as_str)
+    }
+}
 
-    Some(format!("{:.2}GB / {:.2}GB ({}%)", used_gb, total_gb, percentage))
+pub fn get_username() -> Result<String> {
+    env_var(&["USER", "LOGNAME"]).or_else(|err| username_from_passwd().map_err(|_| err))
 }
 
-pub fn get_desktop_environment() -> Option<String> {
-    let keys = ["XDG_CURRENT_DESKTOP", "DESKTOP_SESSION", "GDMSESSION"];
+/// Containers and `env -i` often run without `$USER`
+fn username_from_passwd() -> Result<String> {
+    let status = read("/proc/self/status")?;
+    let uid = status
+        .lines()
+        .find_map(|line| line.strip_prefix("Uid:"))
+        .and_then(|uids| uids.split_whitespace().next())
+        .ok_or_else(|| Error::parse("/proc/self/status", "no Uid line"))?;
+
+    let passwd = read("/etc/passwd")?;
+    passwd
+        .lines()
+        .find_map(|entry| {
+            let mut fields = entry.split(':');
+            let name = fields.next()?;
+            (fields.nth(1)? == uid).then(|| name.to_string())
+        })
+        .ok_or(Error::NotFound("entry for our uid in /etc/passwd"))
+}
 
-    for key in keys.iter() {
-        if let Ok(val) = env::var(key) {
-            if !val.is_empty() {
-                return Some(val);
+pub fn get_hostname() -> Result<String> {
+    sys_info::hostname()
+        .map_err(|err| Error::Sys(err.to_string()))
+        .and_then(|name| {
+            if name.is_empty() {
+                Err(Error::Sys("gethostname returned an empty name".to_string()))
+            } else {
+                Ok(name)
             }
-        }
-    }
-
-    None
+        })
+        .or_else(|err| read_trimmed("/proc/sys/kernel/hostname").map_err(|_| err))
+        .or_else(|err| read_trimmed("/etc/hostname").map_err(|_| err))
 }
 
-pub fn get_shell() -> Option<String> {
-    std::env::var("SHELL")
-        .ok()
-        .as_deref()
-        .and_then(|shell| shell.split('/').last())
-        .map(|s| s.to_string())
+/// First non-empty line of a file
+fn read_trimmed(path: impl AsRef<Path>) -> Result<String> {
+    let path = path.as_ref();
+    read(path)?
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(str::to_string)
+        .ok_or_else(|| Error::parse(path.display(), "empty file"))
 }
 
-pub fn get_terminal() -> Option<String> {
-    std::env::var("TERM")
-        .ok()
-        .as_deref()
-        .map(|s| s.to_string())
+/// Tries `/etc/os-release`, `/usr/lib/os-release`, `/etc/lsb-release` and finally any `/etc/*-release`.
+/// The error is always the one from `/etc/os-release`, the file every modern distro ships
+pub fn get_distro() -> Result<Distro> {
+    read("/etc/os-release")
+        .or_else(|err| read("/usr/lib/os-release").map_err(|_| err))
+        .and_then(|contents| parse_os_release(&contents))
+        .or_else(|err| {
+            read("/etc/lsb-release")
+                .and_then(|contents| parse_lsb_release(&contents))
+                .map_err(|_| err)
+        })
+        .or_else(|err| distro_from_release_files().map_err(|_| err))
 }
 
-
-pub fn get_resolution() -> Option<String> {
-    if let Ok(output) = Command::new("xrandr").output() {
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        if let Some(line) = stdout.lines().find(|l| l.contains('*') && l.contains('x')) {
-            if let Some(res) = line.split_whitespace().find(|s| s.contains('x')) {
-                return Some(res.to_string());
+/// A shell-style value: matching outer quotes are dropped, and inside double quotes a backslash
+/// escapes `$`, `"`, `\` and `` ` ``, as os-release(5) allows
+fn unquote(value: &str) -> String {
+    if let Some(inner) = value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')) {
+        return inner.to_string();
+    }
+    let Some(inner) = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) else {
+        return value.to_string();
+    };
+
+    let mut unquoted = String::with_capacity(inner.len());
+    let mut chars = inner.chars().peekable();
+    while let Some(c) = chars.next() {
+        match chars.peek() {
+            Some(&next @ ('$' | '"' | '\\' | '`')) if c == '\\' => {
+                unquoted.push(next);
+                chars.next();
             }
+            _ => unquoted.push(c),
         }
     }
+    unquoted
+}
 
-    if let Ok(output) = Command::new("xdpyinfo").output() {
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        if let Some(line) = stdout.lines().find(|l| l.trim_start().starts_with("dimensions:")) {
-            return line
-                .split(':')
-                .nth(1)
-                .and_then(|dims| dims.trim().split_whitespace().next())
-                .map(|res| res.to_string());
+/// `KEY=value` pairs with optional quotes, as used by os-release and lsb-release
+fn parse_release_pairs(contents: &str) -> impl Iterator<Item = (&str, String)> {
+    contents.lines().filter_map(|line| {
+        let line = line.trim();
+        if line.starts_with('#') {
+            return None;
         }
-    }
+        let (key, value) = line.split_once('=')?;
+        Some((key.trim(), unquote(value.trim())))
+    })
+}
 
-    if env::var("WAYLAND_DISPLAY").is_ok() {
-        if let Ok(output) = Command::new("wlr-randr").output() {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            if let Some(line) = stdout.lines().find(|l| l.contains("current") && l.contains('x')) {
-                if let Some(res) = line.split_whitespace().find(|s| s.contains('x')) {
-                    return Some(res.to_string());
-                }
-            }
+fn parse_os_release(contents: &str) -> Result<Distro> {
+    let (mut name, mut pretty_name, mut id, mut id_like) = (None, None, None, Vec::new());
+
+    for (key, value) in parse_release_pairs(contents) {
+        match key {
+            "NAME" => name = Some(value),
+            "PRETTY_NAME" => pretty_name = Some(value),
+            "ID" => id = Some(value),
+            "ID_LIKE" => id_like = value.split_whitespace().map(str::to_string).collect(),
+            _ => {}
         }
     }
 
-    None
+    Ok(Distro {
+        name: pretty_name
+            .or(name)
+            .ok_or_else(|| Error::parse("os-release", "neither PRETTY_NAME nor NAME is set"))?,
+        id,
+        id_like,
+    })
 }
 
-pub fn get_load_average() -> Option<String> {
-    if let Ok(contents) = fs::read_to_string("/proc/loadavg") {
-        let parts: Vec<&str> = contents.split_whitespace().collect();
-        if parts.len() >= 3 {
-            return Some(format!("{} {} {}", parts[0], parts[1], parts[2]));
+fn parse_lsb_release(contents: &str) -> Result<Distro> {
+    let (mut id, mut description) = (None, None);
+
+    for (key, value) in parse_release_pairs(contents) {
+        match key {
+            "DISTRIB_ID" => id = Some(value),
+            "DISTRIB_DESCRIPTION" => description = Some(value),
+            _ => {}
         }
     }
-    None
-}
 
-pub fn get_users_count() -> Option<String> {
-    if let Ok(output) = Command::new("who").output() {
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let count = stdout.lines().count();
-        return Some(count.to_string());
-    }
-    None
+    Ok(Distro {
+        name: description.or_else(|| id.clone()).ok_or_else(|| {
+            Error::parse(
+                "/etc/lsb-release",
+                "neither DISTRIB_DESCRIPTION nor DISTRIB_ID is set",
+            )
+        })?,
+        id: id.map(|id| id.to_lowercase()),
+        id_like: Vec::new(),
+    })
 }
 
-pub fn get_battery_info() -> String {
-    if let Ok(entries) = fs::read_dir("/sys/class/power_supply") {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.file_name().unwrap_or_default().to_string_lossy().starts_with("BAT") {
-                if let Ok(capacity) = fs::read_to_string(path.join("capacity")) {
-                    if let Ok(status) = fs::read_to_string(path.join("status")) {
-                        return format!("{}% [{}]", capacity.trim(), status.trim());
-                    }
-                }
+/// Legacy files like `/etc/redhat-release` ("Fedora release 38") or `/etc/alpine-release` ("3.18.0")
+fn distro_from_release_files() -> Result<Distro> {
+    let mut paths: Vec<_> = read_dir("/etc")?
+        .flatten()
+        .map(|entry| entry.path())
+        .collect();
+    paths.sort();
+
+    paths
+        .iter()
+        .find_map(|path| {
+            let file_name = path.file_name()?.to_str()?;
+            let id = file_name.strip_suffix("-release")?;
+            if matches!(id, "os" | "lsb" | "system") {
+                return None;
             }
+
+            let line = read_trimmed(path).ok()?;
+            let name = if line.starts_with(|c: char| c.is_ascii_digit()) {
+                format!("{} {}", id, line)
+            } else {
+                line
+            };
+
+            Some(Distro {
+                name,
+                id: Some(id.to_lowercase()),
+                id_like: Vec::new(),
+            })
+        })
+        .ok_or(Error::NotFound("/etc/*-release file"))
+}
+
+pub fn get_kernel_version() -> Result<String> {
+    sys_info::os_release().or_else(|_| {
+        // /proc may be missing in a chroot, uname(2) always works
+        let mut name: libc::utsname = unsafe { std::mem::zeroed() };
+        if unsafe { libc::uname(&mut name) } != 0 {
+            return Err(Error::Sys(format!(
+                "uname: {}",
+                std::io::Error::last_os_error()
+            )));
         }
-    }
-    "Подключен к сети".to_string()
+        let release = unsafe { CStr::from_ptr(name.release.as_ptr()) };
+        Ok(release.to_string_lossy().into_owned())
+    })
 }
 
-pub fn get_temperature() -> Option<String> {
-    if let Ok(temp_str) = fs::read_to_string("/sys/class/thermal/thermal_zone0/temp") {
-        if let Ok(temp) = temp_str.trim().parse::<i32>() {
-            return Some(format!("{}°C", temp / 1000));
+pub fn get_uptime() -> Result<Duration> {
+    let from_proc = read("/proc/uptime").and_then(|contents| {
+        // первые числа секунды uptime
+        let first_part = contents.split_whitespace().next().unwrap_or_default();
+        let seconds_float: f64 = first_part.parse().map_err(|_| {
+            Error::parse("/proc/uptime", format!("`{}` is not a number", first_part))
+        })?;
+        Duration::try_from_secs_f64(seconds_float)
+            .map_err(|err| Error::parse("/proc/uptime", err.to_string()))
+    });
+
+    from_proc.or_else(|err| {
+        let mut info: libc::sysinfo = unsafe { std::mem::zeroed() };
+        if unsafe { libc::sysinfo(&mut info) } != 0 {
+            return Err(err);
         }
-    }
-    None
+        u64::try_from(info.uptime)
+            .map(Duration::from_secs)
+            .map_err(|_| err)
+    })
 }
 
-pub fn get_processes_count() -> Option<String> {
-    if let Ok(entries) = fs::read_dir("/proc") {
-        let count = entries
+pub fn get_load_average() -> Result<[f64; 3]> {
+    let contents = read("/proc/loadavg")?;
+    let mut parts = contents
+        .split_whitespace()
+        .map(|part| part.parse::<f64>().ok());
+    let mut next = || {
+        parts
+            .next()
             .flatten()
-            .filter(|entry| {
-                entry.file_name().to_string_lossy().chars().all(|c| c.is_ascii_digit())
-            })
-            .count();
-        return Some(count.to_string());
-    }
-    None
+            .ok_or_else(|| Error::parse("/proc/loadavg", "expected three load averages"))
+    };
+    Ok([next()?, next()?, next()?])
 }
 
-pub fn get_cpu_usage() -> Option<String> {
-    if let Ok(contents) = fs::read_to_string("/proc/stat") {
-        if let Some(cpu_line) = contents.lines().next() {
-            let parts: Vec<&str> = cpu_line.split_whitespace().collect();
-            if parts.len() >= 8 {
-                let idle: u64 = parts[4].parse().unwrap_or(0);
-                let total: u64 = parts[1..8].iter()
-                    .map(|s| s.parse::<u64>().unwrap_or(0))
-                    .sum();
-                if total > 0 {
-                    let usage = 100 - (idle * 100 / total);
-                    return Some(format!("{}%", usage));
-                }
-            }
-        }
-    }
-    Some("?".to_string())
+pub fn get_users_count() -> Result<usize> {
+    let stdout = run("who", &[])?;
+    Ok(stdout.lines().count())
 }
 
-pub fn get_network_info() -> String {
-    if let Ok(output) = Command::new("ip").args(&["route", "show", "default"]).output() {
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        if let Some(line) = stdout.lines().next() {
-            if let Some(interface) = line.split_whitespace().nth(4) {
-                if let Ok(ip_output) = Command::new("ip").args(&["addr", "show", interface]).output() {
-                    let ip_stdout = String::from_utf8_lossy(&ip_output.stdout);
-                    for ip_line in ip_stdout.lines() {
-                        if ip_line.contains("inet ") && !ip_line.contains("127.0.0.1") {
-                            if let Some(ip) = ip_line.split_whitespace().nth(1) {
-                                if let Some(clean_ip) = ip.split('/').next() {
-                                    return format!("{} ({})", interface, clean_ip);
-                                }
-                            }
-                        }
-                    }
-                }
-                return format!("{}", interface);
-            }
-        }
-    }
-    "Нет соединения".to_string()
+pub fn get_processes_count() -> Result<usize> {
+    let count = read_dir("/proc")?
+        .flatten()
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_string_lossy()
+                .chars()
+                .all(|c| c.is_ascii_digit())
+        })
+        .count();
+    Ok(count)
+}
+
+pub fn get_locale_info() -> Result<String> {
+    env_var(&["LANG"])
 }
 
-pub fn get_locale_info() -> Option<String> {
-    env::var("LANG").ok()
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn os_release_values_lose_their_quotes_and_escapes() {
+        let distro = parse_os_release(
+            "# comment\n\
+             NAME=\"Fedora Linux\"\n\
+             PRETTY_NAME=\"Fedora Linux 40 (\\\"Workstation\\\" \\$EDITION)\"\n\
+             ID=fedora\n\
+             ID_LIKE='rhel centos'\n",
+        )
+        .unwrap();
+        assert_eq!(distro.name, "Fedora Linux 40 (\"Workstation\" $EDITION)");
+        assert_eq!(distro.id.as_deref(), Some("fedora"));
+        assert_eq!(distro.id_like, ["rhel", "centos"]);
+    }
+
+    #[test]
+    fn os_release_keeps_unmatched_quotes_and_other_backslashes() {
+        assert_eq!(unquote("\"Arch Linux"), "\"Arch Linux");
+        assert_eq!(unquote("'it\\'s'"), "it\\'s");
+        assert_eq!(unquote("\"C:\\path\""), "C:\\path");
+        assert_eq!(unquote("\""), "\"");
+    }
+
+    #[test]
+    fn os_release_falls_back_to_name() {
+        let distro = parse_os_release("NAME=Alpine\nID=alpine").unwrap();
+        assert_eq!(distro.name, "Alpine");
+        assert!(parse_os_release("ID=void").is_err());
+    }
+
+    #[test]
+    fn lsb_release_prefers_the_description_and_lowercases_the_id() {
+        let distro =
+            parse_lsb_release("DISTRIB_ID=Ubuntu\nDISTRIB_DESCRIPTION=\"Ubuntu 24.04 LTS\"")
+                .unwrap();
+        assert_eq!(distro.name, "Ubuntu 24.04 LTS");
+        assert_eq!(distro.id.as_deref(), Some("ubuntu"));
+        assert_eq!(
+            parse_lsb_release("DISTRIB_ID=Ubuntu").unwrap().name,
+            "Ubuntu"
+        );
+        assert!(parse_lsb_release("DISTRIB_RELEASE=24.04").is_err());
+    }
+}
@@ -0,0 +1,191 @@
+use crate::config::{COLOR_NAMES, Color, NC};
+use std::path::Path;
+use std::{fs, io};
+
+/// Built-in ASCII art; `{name}` placeholders switch to a palette color,
+/// `{logo}` to the configured logo color and `{reset}` back to the default
+pub struct Logo {
+    pub name: &'static str,
+    /// os-release `ID`s this logo is picked for by `auto`
+    distros: &'static [&'static str],
+    art: &'static str,
+}
+
+pub const DEFAULT_LOGO: &str = "fortuna";
+
+/// Picks the logo matching the running distro, falling back to [`DEFAULT_LOGO`]
+pub const AUTO_LOGO: &str = "auto";
+
+const LOGOS: &[Logo] = &[
+    Logo {
+        name: "fortuna",
+        distros: &[],
+        art: r#"
+        {logo}░ ░░░░                            ░░░░░░
+        {logo}░░░░░░                            ░░░░░░
+        {logo}░░░▓▓░░░░   ░░░░░░░░░░░░░░░░░  ░░░░▒▒░░
+         {logo}░░▓▓▓▓▓▓░░░░░▒▒▒▒▒▒▒▒▒▒▒▒░░░░░▒▒▒▒▒▒░░
+           {logo}░▓▓▓▓▓▓▓▓▓░░░▒▒▒▒▒▒▒▒░░░▒▒▒▒▒▒▒▒▒▒░
+           {logo}░░▓▓▓▓▓▓▓▓▓▓▒░░▒▒▒▒░░▒▒▒▒▒▒▒▒▒▒▒░░
+        {logo}░░░▒▓▓░░░░░░░░░▓▓░░▒▒░░▒▒░░░░░░░░░▒▒░░░░
+        {logo}░░▓▓▓░   ░██░░░░▓▓░░░▒▒▒░░▒░██░░░░░▒▒▒░░
+        {logo}░░▓▓░░ ░░██░░░█░░▓▓░░▒▒░░▒░░░██░  ░░▒▒░
+        {logo}░░▓▓░  ░░███▓█▓░░▓▓░░▒▒░░██▓███░░ ░░▒▒░░
+        {logo}░░▓▓░░   ░░█▓░░░░▓▓░░▒▒░░░░▓█░░   ░░▒▒░░
+         {logo}░░▓▓░░░░  ░░░░▒▓▓░░░░▒▒░░░░░░  ░░░░▒▒░░░
+        {logo}░░░░▓▓▓▓░░░░░▓▓▓▒░░▒▒░░▒▒▒▒░░░░░▒▒▒▒░░░░
+           {logo}░░░░▓▓▓▓▓▓▒░░░░░▒▒░░░░░▒▒▒▒▒▒▒░░░░
+           {logo}░░░▒▒░░░░░▒▒▒▒▒░░░░▒▒▒▒▒░░░░░▒▒░░░
+           {logo}░░░░░▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒░░░░░
+              {logo}░░░░▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒░░░░
+                 {logo}░░▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒░░░
+                 {logo}░░░░▒▒▒▒▒▒▒▒▒▒▒▒▒▒░░░░
+                   {logo}░░░░▒▒▒▒▒▒▒▒▒▒░░░░
+                      {logo}░░░▒▒▒▒▒▒░░░
+                      {logo}░░░░░▒▒░░░░░
+                         {logo}░░░░░░"#,
+    },
+    Logo {
+        name: "tux",
+        distros: &["linux"],
+        art: r#"
+        {white}#####
+       {white}#######
+       {white}##{yellow}O{white}#{yellow}O{white}##
+       {white}#{yellow}#####{white}#
+     {white}##{light_gray}##{yellow}###{light_gray}##{white}##
+    {white}#{light_gray}##########{white}##
+   {white}#{light_gray}############{white}##
+   {white}#{light_gray}############{white}###
+  {yellow}##{white}#{light_gray}###########{white}##{yellow}#
+{yellow}######{white}#{light_gray}#######{white}#{yellow}######
+{yellow}#######{white}#{light_gray}#####{white}#{yellow}#######
+  {yellow}#####{white}#######{yellow}#####"#,
+    },
+    Logo {
+        name: "arch",
+        distros: &["arch", "archarm", "artix", "endeavouros", "manjaro"],
+        art: r#"
+{cyan}       /\
+{cyan}      /  \
+{cyan}     /\   \
+{cyan}    /      \
+{cyan}   /   ,,   \
+{cyan}  /   |  |  -\
+{cyan} /_-''    ''-_\"#,
+    },
+    Logo {
+        name: "debian",
+        distros: &["debian", "raspbian"],
+        art: r#"
+{red}  _____
+{red} /  __ \
+{red}|  /    |
+{red}|  \___-
+{red}-_
+{red}  --_"#,
+    },
+    Logo {
+        name: "ubuntu",
+        distros: &["ubuntu", "pop", "linuxmint", "elementary"],
+        art: r#"
+{yellow}         _
+{yellow}     ---(_)
+{yellow} _/  ---  \
+{yellow}(_) |   |
+{yellow}  \  --- _/
+{yellow}     ---(_)"#,
+    },
+    Logo {
+        name: "fedora",
+        distros: &["fedora", "rhel", "centos", "rocky", "almalinux"],
+        art: r#"
+{blue}        ,'''''.
+{blue}       |   ,.  |
+{blue}       |  |  '_'
+{blue}  ,....|  |..
+{blue}.'  ,_;|   ..'
+{blue}|  |   |  |
+{blue}|  ',_,'  |
+{blue} '.     ,'
+{blue}   '''''"#,
+    },
+    Logo {
+        name: "alpine",
+        distros: &["alpine"],
+        art: r#"
+{blue}   /\ /\
+{blue}  // \  \
+{blue} //   \  \
+{blue}///    \  \
+{blue}//      \  \
+{blue}         \"#,
+    },
+];
+
+pub fn names() -> impl Iterator<Item = &'static str> {
+    [AUTO_LOGO].into_iter().chain(LOGOS.iter().map(|logo| logo.name))
+}
+
+pub fn find(name: &str) -> Option<&'static Logo> {
+    LOGOS.iter().find(|logo| logo.name == name)
+}
+
+/// First logo claiming one of `ids`, most specific id first
+pub fn for_distro<'a>(mut ids: impl Iterator<Item = &'a str>) -> Option<&'static Logo> {
+    ids.find_map(|id| LOGOS.iter().find(|logo| logo.distros.contains(&id)))
+}
+
+impl Logo {
+    pub fn render(&self, logo_color: &Color) -> Vec<String> {
+        render(self.art, logo_color)
+    }
+}
+
+/// Custom art from disk, using the same placeholders as the built-in logos
+pub fn load_file(path: &Path, logo_color: &Color) -> io::Result<Vec<String>> {
+    let art = fs::read_to_string(path)?;
+    Ok(render(&art, logo_color))
+}
+
+fn render(art: &str, logo_color: &Color) -> Vec<String> {
+    art.trim_start_matches('\n')
+        .lines()
+        .map(|line| format!("{}{}", substitute(line, logo_color), NC))
+        .collect()
+}
+
+fn substitute(line: &str, logo_color: &Color) -> String {
+    let mut result = String::with_capacity(line.len());
+    let mut rest = line;
+
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let placeholder = rest.find('}').map(|end| (&rest[1..end], end));
+        let escape = placeholder.and_then(|(name, _)| match name {
+            "logo" => Some(logo_color.to_string()),
+            "reset" => Some(NC.to_string()),
+            _ => COLOR_NAMES
+                .iter()
+                .find(|(color, _)| *color == name)
+                .map(|(_, escape)| escape.to_string()),
+        });
+
+        match (escape, placeholder) {
+            (Some(escape), Some((_, end))) => {
+                result.push_str(&escape);
+                rest = &rest[end + 1..];
+            }
+            // not a placeholder, keep the brace as is
+            _ => {
+                result.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    result.push_str(rest);
+    result
+}
@@ -1,10 +1,15 @@
-use crate::config::{BLACK, BLUE, CYAN, DARK_GRAY, GAP, GREEN, LIGHT_BLUE, LIGHT_GRAY, NC, PURPLE, RED, WHITE, YELLOW};
-use fortfetch::{get_battery_info, get_cpu_model, get_cpu_usage, get_desktop_environment, get_disk_info, get_gpu_model, get_load_average, get_locale_info, get_network_info, get_package_count, get_processes_count, get_resolution, get_shell, get_temperature, get_terminal, get_users_count, Uptime};
-use std::process::Command;
-use std::fs;
-use std::env;
+use crate::args::{Args, Parsed};
+use crate::config::{Config, BLACK, BLUE, CYAN, GREEN, LIGHT_GRAY, NC, PURPLE, RED, WHITE, YELLOW};
+use fortfetch::i18n::{Lang, Messages};
+use fortfetch::module::{self, Module};
+use fortfetch::{SystemInfo, Timing};
+use std::process;
 
+mod args;
+mod bar;
 mod config;
+mod json;
+mod logo;
 
 fn get_string_length(s: &str) -> usize {
     let mut result = 0;
@@ -14,7 +19,7 @@ fn get_string_length(s: &str) -> usize {
         if ch == '\x1b' {
             if chars.peek() == Some(&'[') {
                 chars.next();
-                while let Some(c) = chars.next() {
+                for c in chars.by_ref() {
                     if c.is_ascii_alphabetic() {
                         break;
                     }
@@ -28,121 +33,217 @@ fn get_string_length(s: &str) -> usize {
     result
 }
 
-fn get_info() -> Vec<String> {
+/// `failures` are the timings whose reasons `--debug` shows next to the affected lines
+fn get_info(config: &Config, info: &SystemInfo, messages: &'static Messages, failures: &[Timing]) -> Vec<String> {
+    let colors = &config.colors;
+    let (accent, key, value) = (&colors.accent, &colors.key, &colors.value);
+    let settings = config.settings();
     let mut info_lines = Vec::new();
 
-    let hostname = sys_info::hostname().unwrap_or("?".to_string());
-    let username = env::var("USER").unwrap_or_else(|_| "user".to_string());
-    let ditro = sys_info::linux_os_release().unwrap().pretty_name.unwrap_or("?".to_string());
-    let kernel_version = sys_info::os_release().unwrap_or("?".to_string());
-    let uptime = Uptime::new().unwrap();
-    let package_count = get_package_count()
-        .map(|n| n.to_string())
-        .unwrap_or_else(|| "?".to_string());
-    let cpu = get_cpu_model().unwrap_or("?".to_string());
-    let gpu = get_gpu_model().unwrap_or("?".to_string());
-    let disk = get_disk_info().unwrap_or("?".to_string());
-    let de = get_desktop_environment().unwrap_or("?".to_string());
-    let shell = get_shell().unwrap_or("?".to_string());
-    let terminal = get_terminal().unwrap_or("?".to_string());
-    let resolution = get_resolution().unwrap_or("?".to_string());
-    let load_avg = get_load_average().unwrap_or("?".to_string());
-    let users_count = get_users_count().unwrap_or("?".to_string());
-    let battery = get_battery_info();
-    let temperature = get_temperature().unwrap_or("?".to_string());
-    let processes = get_processes_count().unwrap_or("?".to_string());
-    let cpu_usage = get_cpu_usage().unwrap_or("?".to_string());
-    let network = get_network_info();
-    let locale = get_locale_info().unwrap_or("?".to_string());
-
-    info_lines.push(format!("{}{}{}@{}{}{}", 
-        WHITE, username, LIGHT_GRAY, LIGHT_BLUE, hostname, NC));
-    
-    info_lines.push(format!("{}{}{}",
-        DARK_GRAY, "─".repeat(45), NC));
-    
-    info_lines.push(format!("{} {}Система{}", LIGHT_BLUE, WHITE, NC));
-    info_lines.push(format!("{}├─ {}Абонент{}     {}{}{}", 
-        LIGHT_BLUE, WHITE, NC, LIGHT_GRAY, hostname, NC));
-    info_lines.push(format!("{}├─ {}Тариф{}      {}{}{}", 
-        LIGHT_BLUE, WHITE, NC, LIGHT_GRAY, ditro, NC));
-    info_lines.push(format!("{}├─ {}Прошивка{}   {}{}{}", 
-        LIGHT_BLUE, WHITE, NC, LIGHT_GRAY, kernel_version, NC));
-    info_lines.push(format!("{}├─ {}Пополнение{} {}{}назад{}", 
-        LIGHT_BLUE, WHITE, NC, LIGHT_GRAY, uptime.get(), NC));
-    info_lines.push(format!("{}├─ {}Вирусов{}    {}{}{}", 
-        LIGHT_BLUE, WHITE, NC, LIGHT_GRAY, package_count, NC));
-    info_lines.push(format!("{}└─ {}Оболочка{}  {}{}{}", 
-        LIGHT_BLUE, WHITE, NC, LIGHT_GRAY, shell, NC));
-    
-    info_lines.push("".to_string());
-    
-    info_lines.push(format!("{} {}Железо{}", LIGHT_BLUE, WHITE, NC));
-    info_lines.push(format!("{}├─ {}ЦП{}         {}{}{}", 
-        LIGHT_BLUE, WHITE, NC, LIGHT_GRAY, cpu, NC));
-    info_lines.push(format!("{}├─ {}ГПУ{}        {}{}{}", 
-        LIGHT_BLUE, WHITE, NC, LIGHT_GRAY, gpu, NC));
-    info_lines.push(format!("{}└─ {}Дискета{}    {}{}{}", 
-        LIGHT_BLUE, WHITE, NC, LIGHT_GRAY, disk, NC));
-    
-    info_lines.push("".to_string());
-    
-    info_lines.push(format!("{} {}Производительность{}", LIGHT_BLUE, WHITE, NC));
-    info_lines.push(format!("{}├─ {}Загрузка ЦП{} {}{}{}", 
-        LIGHT_BLUE, WHITE, NC, LIGHT_GRAY, cpu_usage, NC));
-    info_lines.push(format!("{}├─ {}Температура{} {}{}{}", 
-        LIGHT_BLUE, WHITE, NC, LIGHT_GRAY, temperature, NC));
-    info_lines.push(format!("{}└─ {}Нагрузка{}   {}{}{}", 
-        LIGHT_BLUE, WHITE, NC, LIGHT_GRAY, load_avg, NC));
-    
-    info_lines.push("".to_string());
-    
-    info_lines.push(format!("{} {}Окружение{}", LIGHT_BLUE, WHITE, NC));
-    info_lines.push(format!("{}├─ {}Админка{}    {}{}{}", 
-        LIGHT_BLUE, WHITE, NC, LIGHT_GRAY, de, NC));
-    info_lines.push(format!("{}├─ {}Экран{}      {}{}{}", 
-        LIGHT_BLUE, WHITE, NC, LIGHT_GRAY, resolution, NC));
-    info_lines.push(format!("{}├─ {}Терминал{}   {}{}{}", 
-        LIGHT_BLUE, WHITE, NC, LIGHT_GRAY, terminal, NC));
-    info_lines.push(format!("{}├─ {}Локаль{}     {}{}{}", 
-        LIGHT_BLUE, WHITE, NC, LIGHT_GRAY, locale, NC));
-    info_lines.push(format!("{}├─ {}Процессов{}  {}{}{}", 
-        LIGHT_BLUE, WHITE, NC, LIGHT_GRAY, processes, NC));
-    info_lines.push(format!("{}├─ {}Юзеров{}     {}{}{}", 
-        LIGHT_BLUE, WHITE, NC, LIGHT_GRAY, users_count, NC));
-    info_lines.push(format!("{}└─ {}Сеть{}       {}{}{}", 
-        LIGHT_BLUE, WHITE, NC, LIGHT_GRAY, network, NC));
-    
-    if !battery.contains("Подключен к сети") {
-        info_lines.push("".to_string());
-        info_lines.push(format!("{} {}Батарея{}     {}{}{}", 
-            LIGHT_BLUE, WHITE, NC, LIGHT_GRAY, battery, NC));
+    if config.header {
+        let hostname = info.hostname.as_deref().unwrap_or("?");
+        let username = info.username.as_deref().unwrap_or("user");
+
+        info_lines.push(format!("{}{}{}@{}{}{}",
+            key, username, value, accent, hostname, NC));
+
+        info_lines.push(format!("{}{}{}",
+            colors.separator, "─".repeat(45), NC));
     }
-    
-    info_lines.push("".to_string());
-    
-    let mut palette = format!("{}Цвета: ", LIGHT_GRAY);
-    let colors = [BLACK, RED, GREEN, YELLOW, BLUE, PURPLE, CYAN, WHITE];
-    for &color in &colors {
-        palette.push_str(&format!("{}███{}", color, NC));
+
+    let sections: Vec<(&str, Vec<(String, String)>)> = config.sections.iter()
+        .map(|section| {
+            let lines: Vec<_> = section.modules.iter()
+                .flat_map(|module| {
+                    let failure = failures.iter().find(|t| t.name == module.source() && t.failed());
+                    module.lines(info, messages, &settings).into_iter().map(move |line| {
+                        let mut text = line.text;
+                        if let Some(gauge) = line.gauge.filter(|_| config.bar.enabled) {
+                            text.push_str(&format!(" {}", bar::render(gauge, &config.bar, colors)));
+                        }
+                        if let Some(failure) = failure {
+                            text.push_str(&format!(" {}({})", LIGHT_GRAY, failure.status));
+                        }
+                        (line.label, text)
+                    })
+                })
+                .collect();
+            (config::section_title(section, messages), lines)
+        })
+        .filter(|(_, lines)| !lines.is_empty())
+        .collect();
+
+    let label_width = sections.iter()
+        .flat_map(|(_, lines)| lines.iter().map(|(label, _)| label.chars().count()))
+        .max()
+        .unwrap_or(0)
+        .min(config.key_width);
+
+    for (index, (title, lines)) in sections.iter().enumerate() {
+        if index > 0 {
+            info_lines.push("".to_string());
+        }
+
+        info_lines.push(format!("{} {}{}{}", accent, key, title, NC));
+        for (i, (label, text)) in lines.iter().enumerate() {
+            let branch = if i + 1 == lines.len() { "└─" } else { "├─" };
+            let label: String = label.chars().take(label_width).collect();
+            let padding = " ".repeat(label_width - label.chars().count() + 1);
+            info_lines.push(format!("{}{} {}{}{}{}{}{}{}",
+                accent, branch, key, label, NC, padding, value, text, NC));
+        }
+    }
+
+    if config.palette {
+        info_lines.push("".to_string());
+
+        let mut palette = format!("{}{} ", LIGHT_GRAY, messages.colors);
+        let colors = [BLACK, RED, GREEN, YELLOW, BLUE, PURPLE, CYAN, WHITE];
+        for &color in &colors {
+            palette.push_str(&format!("{}███{}", color, NC));
+        }
+        info_lines.push(palette);
     }
-    info_lines.push(palette);
 
     info_lines
 }
 
 fn main() {
-    let logo = config::get_logo();
-    let info_lines = get_info();
-
-    let mut max_logo_width = 0;
-    for line in &logo {
-        let current_width = get_string_length(line);
-        if current_width > max_logo_width {
-            max_logo_width = current_width;
+    let args = match Args::parse() {
+        Ok(Parsed::Run(args)) => args,
+        Ok(Parsed::Exit(message)) => {
+            println!("{}", message);
+            return;
+        }
+        Err(e) => {
+            eprintln!("fortfetch: {}", e);
+            process::exit(2);
+        }
+    };
+
+    let config = Config::load().unwrap_or_else(|e| {
+        eprintln!("fortfetch: {}", e);
+        process::exit(1);
+    });
+
+    let settings = config.settings();
+    let (info, timings) = if args.json {
+        SystemInfo::collect_with(&settings)
+    } else {
+        SystemInfo::collect_modules(&displayed_modules(&args, &config), &settings)
+    };
+
+    if args.strict {
+        check_strict(&timings);
+    }
+
+    if args.json {
+        let mut report = json::Report::from(&info);
+        if args.debug {
+            report.add_errors(&timings);
         }
+        println!("{}", report.to_json());
+    } else {
+        let logo = select_logo(&args, &config, &info).unwrap_or_else(|e| {
+            eprintln!("fortfetch: {}", e);
+            process::exit(1);
+        });
+        let lang = args.lang.or(config.lang).unwrap_or_else(Lang::from_env);
+        let failures = if args.debug { timings.as_slice() } else { &[] };
+        print_fetch(&config, &info, &logo, lang.messages(), failures);
     }
 
+    if args.timings {
+        print_timings(&timings);
+    }
+}
+
+/// Modules the fetch layout actually shows, plus what the header and logo need
+fn displayed_modules(args: &Args, config: &Config) -> Vec<&'static dyn Module> {
+    let mut names = Vec::new();
+    if config.header {
+        names.extend(["username", "hostname"]);
+    }
+    if !args.no_logo {
+        names.push("distro");
+    }
+
+    let mut modules: Vec<&'static dyn Module> = names.into_iter().filter_map(module::find).collect();
+    for &module in config.sections.iter().flat_map(|section| &section.modules) {
+        if !modules.iter().any(|m| m.name() == module.name()) {
+            modules.push(module);
+        }
+    }
+    modules
+}
+
+/// `--strict`: refuse to print a partial fetch
+fn check_strict(timings: &[Timing]) {
+    let failed: Vec<&Timing> = timings.iter().filter(|timing| timing.failed()).collect();
+    if failed.is_empty() {
+        return;
+    }
+
+    eprintln!("fortfetch: {} detector(s) failed:", failed.len());
+    for timing in failed {
+        eprintln!("  {}: {}", timing.name, timing.status);
+    }
+    process::exit(1);
+}
+
+fn print_timings(timings: &[Timing]) {
+    let width = timings.iter().map(|t| t.name.len()).max().unwrap_or(0);
+
+    for timing in timings {
+        let elapsed = timing.elapsed.as_secs_f64() * 1000.0;
+        let note = if timing.failed() {
+            format!(" ({})", timing.status)
+        } else {
+            String::new()
+        };
+        eprintln!("{:width$}  {:8.2} ms{}", timing.name, elapsed, note, width = width);
+    }
+}
+
+/// `--no-logo` wins over `--logo-file`, which wins over `--logo`, which wins over the config
+fn select_logo(args: &Args, config: &Config, info: &SystemInfo) -> Result<Vec<String>, String> {
+    if args.no_logo {
+        return Ok(Vec::new());
+    }
+
+    let color = &config.colors.logo;
+    let file = match (&args.logo_file, &args.logo) {
+        (Some(file), _) => Some(file),
+        (None, Some(_)) => None,
+        (None, None) => config.logo_file.as_ref(),
+    };
+
+    if let Some(path) = file {
+        return logo::load_file(path, color)
+            .map_err(|e| format!("cannot read logo file {}: {}", path.display(), e));
+    }
+
+    let name = args.logo.as_deref().unwrap_or(&config.logo);
+    let selected = if name == logo::AUTO_LOGO {
+        info.distro.as_ref().and_then(|distro| logo::for_distro(distro.ids()))
+    } else {
+        let names: Vec<&str> = logo::names().collect();
+        Some(logo::find(name).ok_or_else(|| {
+            format!("unknown logo `{}`, expected one of {}", name, names.join(", "))
+        })?)
+    };
+
+    let selected = selected.or_else(|| logo::find(logo::DEFAULT_LOGO)).expect("default logo is built in");
+    Ok(selected.render(color))
+}
+
+fn print_fetch(config: &Config, info: &SystemInfo, logo: &[String], messages: &'static Messages, failures: &[Timing]) {
+    let info_lines = get_info(config, info, messages, failures);
+
+    let max_logo_width = logo.iter().map(|line| get_string_length(line)).max().unwrap_or(0);
+    // without a logo the info column starts at the left edge
+    let gap = if logo.is_empty() { 0 } else { config.gap };
+
     println!();
 
     let max_lines = logo.len().max(info_lines.len());
@@ -151,14 +252,14 @@ fn main() {
         if i < logo.len() {
             let logo_line = &logo[i];
             print!("{}", logo_line);
-            
+
             let logo_line_width = get_string_length(logo_line);
-            let padding = max_logo_width - logo_line_width + GAP;
+            let padding = max_logo_width - logo_line_width + gap;
             print!("{}", " ".repeat(padding));
         } else {
-            print!("{}", " ".repeat(max_logo_width + GAP));
+            print!("{}", " ".repeat(max_logo_width + gap));
         }
-        
+
         if i < info_lines.len() {
             println!("{}", info_lines[i]);
         } else {
@@ -0,0 +1,215 @@
+//! RAM and swap from `/proc/meminfo`, plus zram devices from sysfs
+
+use crate::Usage;
+use crate::error::{Error, Result, read};
+use std::fs;
+
+/// RAM figures from `/proc/meminfo`, in bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Memory {
+    pub total: u64,
+    /// `MemAvailable`: what can be allocated without swapping, page cache included
+    pub available: u64,
+    pub free: u64,
+    pub buffers: u64,
+    /// Page cache, `Shmem` included
+    pub cached: u64,
+    /// tmpfs and shared memory, which cannot be dropped like the rest of the cache
+    pub shmem: u64,
+}
+
+impl Memory {
+    pub fn used(&self) -> u64 {
+        self.total.saturating_sub(self.available)
+    }
+
+    pub fn usage(&self) -> Usage {
+        Usage {
+            used: self.used(),
+            total: self.total,
+        }
+    }
+}
+
+/// Swap figures from `/proc/meminfo`, in bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Swap {
+    pub total: u64,
+    pub free: u64,
+    /// Compressed RAM swap devices, summed; `None` without zram
+    pub zram: Option<Zram>,
+}
+
+impl Swap {
+    pub fn used(&self) -> u64 {
+        self.total.saturating_sub(self.free)
+    }
+
+    pub fn usage(&self) -> Usage {
+        Usage {
+            used: self.used(),
+            total: self.total,
+        }
+    }
+}
+
+/// Totals over every `/sys/block/zram*` device, in bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Zram {
+    /// Configured `disksize`
+    pub size: u64,
+    /// Data swapped out, before compression
+    pub stored: u64,
+    /// RAM the compressed data actually takes
+    pub compressed: u64,
+}
+
+/// `MemTotal:  16314520 kB` pairs, converted to bytes; counts like `HugePages_Total: 0` stay as is
+fn parse_meminfo(contents: &str) -> impl Iterator<Item = (&str, u64)> {
+    contents.lines().filter_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        let mut value = value.split_whitespace();
+        let number: u64 = value.next()?.parse().ok()?;
+        match value.next() {
+            Some("kB") => Some((key, number.checked_mul(1024)?)),
+            None => Some((key, number)),
+            Some(_) => None,
+        }
+    })
+}
+
+fn meminfo_field(contents: &str, key: &str) -> Option<u64> {
+    parse_meminfo(contents).find_map(|(name, value)| (name == key).then_some(value))
+}
+
+pub fn get_memory() -> Result<Memory> {
+    memory_from_meminfo(&read("/proc/meminfo")?)
+}
+
+fn memory_from_meminfo(meminfo: &str) -> Result<Memory> {
+    let field = |key: &'static str| {
+        meminfo_field(meminfo, key)
+            .ok_or_else(|| Error::parse("/proc/meminfo", format!("no `{}` line", key)))
+    };
+
+    let total = field("MemTotal")?;
+    let free = field("MemFree")?;
+    let buffers = field("Buffers").unwrap_or(0);
+    let cached = field("Cached").unwrap_or(0);
+    let shmem = field("Shmem").unwrap_or(0);
+    // kernels before 3.14 have no MemAvailable, so estimate it the way `free` used to
+    let available =
+        field("MemAvailable").unwrap_or_else(|_| (free + buffers + cached).saturating_sub(shmem));
+
+    Ok(Memory {
+        total,
+        available,
+        free,
+        buffers,
+        cached,
+        shmem,
+    })
+}
+
+pub fn get_swap() -> Result<Swap> {
+    let meminfo = read("/proc/meminfo")?;
+    let field = |key: &'static str| {
+        meminfo_field(&meminfo, key)
+            .ok_or_else(|| Error::parse("/proc/meminfo", format!("no `{}` line", key)))
+    };
+
+    Ok(Swap {
+        total: field("SwapTotal")?,
+        free: field("SwapFree")?,
+        zram: zram(),
+    })
+}
+
+/// `disksize` and the first fields of `mm_stat`: `orig_data_size compr_data_size mem_used_total`
+fn zram() -> Option<Zram> {
+    let mut total: Option<Zram> = None;
+
+    for device in fs::read_dir("/sys/block").ok()?.flatten() {
+        if !device.file_name().to_string_lossy().starts_with("zram") {
+            continue;
+        }
+        let path = device.path();
+        let Some(size) = fs::read_to_string(path.join("disksize"))
+            .ok()
+            .and_then(|size| size.trim().parse::<u64>().ok())
+            .filter(|&size| size > 0)
+        else {
+            continue;
+        };
+
+        let stats: Vec<u64> = fs::read_to_string(path.join("mm_stat"))
+            .unwrap_or_default()
+            .split_whitespace()
+            .filter_map(|value| value.parse().ok())
+            .collect();
+
+        let zram = total.get_or_insert(Zram {
+            size: 0,
+            stored: 0,
+            compressed: 0,
+        });
+        zram.size += size;
+        zram.stored += stats.first().copied().unwrap_or(0);
+        zram.compressed += stats.get(2).copied().unwrap_or(0);
+    }
+
+    total
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MEMINFO: &str = "\
+MemTotal:       16314520 kB
+MemFree:         1203400 kB
+MemAvailable:    9876544 kB
+Buffers:          412000 kB
+Cached:          7100000 kB
+SwapTotal:       8388604 kB
+Shmem:            650000 kB
+HugePages_Total:       0
+Hugepagesize:       2048 kB
+";
+
+    #[test]
+    fn meminfo_values_are_bytes() {
+        assert_eq!(meminfo_field(MEMINFO, "MemTotal"), Some(16314520 * 1024));
+        assert_eq!(meminfo_field(MEMINFO, "Hugepagesize"), Some(2048 * 1024));
+        assert_eq!(meminfo_field(MEMINFO, "HugePages_Total"), Some(0));
+        assert_eq!(meminfo_field(MEMINFO, "SwapFree"), None);
+    }
+
+    #[test]
+    fn meminfo_skips_malformed_and_overflowing_lines() {
+        let meminfo = "Broken\nMemFree: lots kB\nMemTotal: 99999999999999999 kB\nCached: 1 MB\n";
+        assert_eq!(parse_meminfo(meminfo).count(), 0);
+    }
+
+    #[test]
+    fn memory_prefers_mem_available() {
+        let memory = memory_from_meminfo(MEMINFO).unwrap();
+        assert_eq!(memory.total, 16314520 * 1024);
+        assert_eq!(memory.available, 9876544 * 1024);
+        assert_eq!(memory.shmem, 650000 * 1024);
+    }
+
+    #[test]
+    fn memory_estimates_available_on_old_kernels() {
+        let old = MEMINFO.replace("MemAvailable:    9876544 kB\n", "");
+        let memory = memory_from_meminfo(&old).unwrap();
+        assert_eq!(
+            memory.available,
+            (1203400 + 412000 + 7100000 - 650000) * 1024
+        );
+        assert!(matches!(
+            memory_from_meminfo("MemFree: 1 kB"),
+            Err(Error::Parse { .. })
+        ));
+    }
+}
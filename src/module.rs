@@ -0,0 +1,809 @@
+//! Info lines as pluggable modules: each one knows how to detect its value and how to show it
+
+use crate::i18n::Messages;
+use crate::{Battery, BatteryStatus, Error, LinkState, Settings, SystemInfo, Units, Usage};
+use std::time::Duration;
+
+/// Stores a detected value into [`SystemInfo`]; built on a worker thread, applied on the caller's
+pub type Update = Box<dyn FnOnce(&mut SystemInfo) + Send>;
+
+pub trait Module: Send + Sync {
+    /// Identifier used in the config layout and `--timings`
+    fn name(&self) -> &'static str;
+
+    fn label(&self, messages: &'static Messages) -> &'static str;
+
+    /// Detector whose value this module shows. Modules sharing one, like `cpu` and `cpu_cache`,
+    /// have it run once and see its failure reported once, under this name
+    fn source(&self) -> &'static str {
+        self.name()
+    }
+
+    /// Run the detector; may block, so it is called off the main thread.
+    /// The error explains an empty field in `--debug` and `--strict`
+    fn collect(&self, settings: &Settings) -> Result<Update, Error>;
+
+    /// Text shown after the label, `None` hides the line entirely
+    fn render(&self, info: &SystemInfo, messages: &Messages, settings: &Settings) -> Option<String>;
+
+    /// How full a usage bar after the text would be; `None` for values that are not a share of something
+    fn gauge(&self, _info: &SystemInfo) -> Option<Gauge> {
+        None
+    }
+
+    /// Every line shown; one unless a module reports several things, like disks
+    fn lines(&self, info: &SystemInfo, messages: &'static Messages, settings: &Settings) -> Vec<Line> {
+        self.render(info, messages, settings)
+            .map(|text| {
+                vec![Line {
+                    label: self.label(messages).to_string(),
+                    text,
+                    gauge: self.gauge(info),
+                }]
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// One row of the info column
+#[derive(Debug, Clone, PartialEq)]
+pub struct Line {
+    pub label: String,
+    pub text: String,
+    pub gauge: Option<Gauge>,
+}
+
+/// Fill level for a usage bar
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Gauge {
+    /// 0 to 100
+    pub percent: f64,
+    /// A full battery is good news, a full disk is not
+    pub higher_is_better: bool,
+}
+
+impl Gauge {
+    /// Memory, disk or CPU in use
+    pub fn used(percent: f64) -> Gauge {
+        Gauge {
+            percent,
+            higher_is_better: false,
+        }
+    }
+
+    /// Battery charge left
+    pub fn charge(percent: f64) -> Gauge {
+        Gauge {
+            percent,
+            higher_is_better: true,
+        }
+    }
+}
+
+/// Shown for values no detector could find
+pub const UNKNOWN: &str = "?";
+
+/// A module backed by one `Option` field of [`SystemInfo`], shown as "?" when it is empty.
+/// A trailing `source "name"` shares the detector of the module called that,
+/// and `gauge |v| ...` gives the fill level of its usage bar
+macro_rules! field_module {
+    ($module:ident, $name:literal, $field:ident, $label:ident, $detect:path, |$value:ident: &$ty:ty, $messages:ident| $render:expr $(, source $source:literal)? $(, gauge |$gauge_value:ident| $gauge:expr)?) => {
+        field_module!($module, $name, $field, $label, $detect, |$value: &$ty, $messages, _settings| $render $(, source $source)? $(, gauge |$gauge_value| $gauge)?);
+    };
+    ($module:ident, $name:literal, $field:ident, $label:ident, $detect:path, |$value:ident: &$ty:ty, $messages:ident, $settings:ident| $render:expr $(, source $source:literal)? $(, gauge |$gauge_value:ident| $gauge:expr)?) => {
+        pub struct $module;
+
+        impl Module for $module {
+            fn name(&self) -> &'static str {
+                $name
+            }
+            $(
+                fn source(&self) -> &'static str {
+                    $source
+                }
+            )?
+
+            fn label(&self, messages: &'static Messages) -> &'static str {
+                messages.$label
+            }
+
+            fn collect(&self, _settings: &Settings) -> Result<Update, Error> {
+                let value = $detect()?;
+                Ok(Box::new(move |info| info.$field = Some(value)))
+            }
+
+            fn render(&self, info: &SystemInfo, $messages: &Messages, $settings: &Settings) -> Option<String> {
+                let text = match &info.$field {
+                    Some($value) => {
+                        let $value: &$ty = $value;
+                        $render
+                    }
+                    None => UNKNOWN.to_string(),
+                };
+                Some(text)
+            }
+            $(
+                fn gauge(&self, info: &SystemInfo) -> Option<Gauge> {
+                    let $gauge_value: &$ty = info.$field.as_ref()?;
+                    $gauge
+                }
+            )?
+        }
+    };
+}
+
+field_module!(UsernameModule, "username", username, username, crate::get_username, |v: &String, _m| v.clone());
+field_module!(HostnameModule, "hostname", hostname, hostname, crate::get_hostname, |v: &String, _m| v.clone());
+field_module!(DistroModule, "distro", distro, distro, crate::get_distro, |v: &crate::Distro, _m| v.name.clone());
+field_module!(KernelModule, "kernel", kernel, kernel, crate::get_kernel_version, |v: &String, _m| v.clone());
+field_module!(UptimeModule, "uptime", uptime, uptime, crate::get_uptime, |v: &Duration, m| format_uptime(*v, m));
+field_module!(PackagesModule, "packages", packages, packages, crate::get_package_count, |v: &Vec<crate::PackageCount>, _m| format_packages(v));
+field_module!(ShellModule, "shell", shell, shell, crate::get_shell, |v: &crate::Shell, _m| with_version(&v.name, v.version.as_deref()));
+field_module!(HostModule, "host", host, host, crate::get_host, |v: &crate::Host, _m| v.model().or_else(|| v.board.clone()).unwrap_or_else(|| UNKNOWN.to_string()));
+field_module!(BiosModule, "bios", host, bios, crate::get_host, |v: &crate::Host, _m| format_bios(v), source "host");
+field_module!(BoardModule, "board", host, board, crate::get_host, |v: &crate::Host, _m| v.board.clone().unwrap_or_else(|| UNKNOWN.to_string()), source "host");
+field_module!(CpuModule, "cpu", cpu, cpu, crate::get_cpu, |v: &crate::Cpu, _m| format_cpu(v));
+field_module!(CpuCacheModule, "cpu_cache", cpu, cpu_cache, crate::get_cpu, |v: &crate::Cpu, _m| format_caches(&v.caches), source "cpu");
+field_module!(GpuModule, "gpu", gpus, gpu, crate::get_gpus, |v: &Vec<crate::Gpu>, m| format_gpus(v, m));
+field_module!(MemoryModule, "memory", memory, memory, crate::get_memory, |v: &crate::Memory, _m, s| format_usage(&v.usage(), s), gauge |v| Some(Gauge::used(v.usage().percent() as f64)));
+field_module!(SwapModule, "swap", swap, swap, crate::get_swap, |v: &crate::Swap, m, s| format_swap(v, m, s), gauge |v| (v.total > 0).then(|| Gauge::used(v.usage().percent() as f64)));
+field_module!(LoadModule, "load", load_average, load, crate::get_load_average, |v: &[f64; 3], _m| format!("{:.2} {:.2} {:.2}", v[0], v[1], v[2]));
+field_module!(DeModule, "de", desktop_environment, de, crate::get_desktop_environment, |v: &crate::DesktopEnvironment, _m| with_version(&v.name, v.version.as_deref()));
+field_module!(WmModule, "wm", window_manager, wm, crate::get_window_manager, |v: &crate::WindowManager, _m| format_window_manager(v));
+field_module!(TerminalModule, "terminal", terminal, terminal, crate::get_terminal, |v: &crate::Terminal, _m| format_terminal(v));
+field_module!(LocaleModule, "locale", locale, locale, crate::get_locale_info, |v: &String, _m| v.clone());
+field_module!(ProcessesModule, "processes", processes, processes, crate::get_processes_count, |v: &usize, _m| v.to_string());
+field_module!(UsersModule, "users", users, users, crate::get_users_count, |v: &usize, _m| v.to_string());
+
+pub struct CpuUsageModule;
+
+impl Module for CpuUsageModule {
+    fn name(&self) -> &'static str {
+        "cpu_usage"
+    }
+
+    fn label(&self, messages: &'static Messages) -> &'static str {
+        messages.cpu_usage
+    }
+
+    /// Sleeps for the sampling interval, which runs alongside every other detector
+    fn collect(&self, settings: &Settings) -> Result<Update, Error> {
+        let usage = crate::get_cpu_usage(settings.cpu_usage.interval)?;
+        Ok(Box::new(move |info| info.cpu_usage = Some(usage)))
+    }
+
+    fn render(&self, info: &SystemInfo, _messages: &Messages, settings: &Settings) -> Option<String> {
+        let text = match &info.cpu_usage {
+            Some(usage) => format_cpu_usage(usage, settings.cpu_usage.per_core),
+            None => UNKNOWN.to_string(),
+        };
+        Some(text)
+    }
+
+    fn gauge(&self, info: &SystemInfo) -> Option<Gauge> {
+        info.cpu_usage.as_ref().map(|usage| Gauge::used(usage.percent()))
+    }
+}
+
+pub struct DiskModule;
+
+impl Module for DiskModule {
+    fn name(&self) -> &'static str {
+        "disk"
+    }
+
+    fn label(&self, messages: &'static Messages) -> &'static str {
+        messages.disk
+    }
+
+    fn collect(&self, settings: &Settings) -> Result<Update, Error> {
+        let disks = crate::get_disks(&settings.disk)?;
+        Ok(Box::new(move |info| info.disks = Some(disks)))
+    }
+
+    /// Every mountpoint on one line, for callers that want a single value
+    fn render(&self, info: &SystemInfo, _messages: &Messages, settings: &Settings) -> Option<String> {
+        let text = match &info.disks {
+            Some(disks) => disks
+                .iter()
+                .map(|disk| format!("{} {}", disk.mountpoint, format_disk(disk, settings)))
+                .collect::<Vec<_>>()
+                .join(", "),
+            None => UNKNOWN.to_string(),
+        };
+        Some(text)
+    }
+
+    /// One line per mountpoint, e.g. "Disk  /home 120.50 GiB / 450.00 GiB (26%) - btrfs";
+    /// the mountpoint goes in the value, where `key_width` cannot cut it off
+    fn lines(&self, info: &SystemInfo, messages: &'static Messages, settings: &Settings) -> Vec<Line> {
+        match &info.disks {
+            Some(disks) => disks
+                .iter()
+                .map(|disk| Line {
+                    label: messages.disk.to_string(),
+                    text: format!("{} {}", disk.mountpoint, format_disk(disk, settings)),
+                    gauge: Some(Gauge::used(disk.usage.percent() as f64)),
+                })
+                .collect(),
+            None => vec![Line {
+                label: messages.disk.to_string(),
+                text: UNKNOWN.to_string(),
+                gauge: None,
+            }],
+        }
+    }
+}
+
+pub struct TemperatureModule;
+
+impl Module for TemperatureModule {
+    fn name(&self) -> &'static str {
+        "temperature"
+    }
+
+    fn label(&self, messages: &'static Messages) -> &'static str {
+        messages.temperature
+    }
+
+    fn collect(&self, settings: &Settings) -> Result<Update, Error> {
+        let sensors = crate::get_sensors(&settings.temperature)?;
+        Ok(Box::new(move |info| info.temperatures = Some(sensors)))
+    }
+
+    /// Every sensor on one line, e.g. "CPU 54°C, NVMe 38°C"
+    fn render(&self, info: &SystemInfo, _messages: &Messages, _settings: &Settings) -> Option<String> {
+        let text = match &info.temperatures {
+            Some(sensors) => sensors
+                .iter()
+                .map(|sensor| format!("{} {:.0}°C", sensor_name(sensor), sensor.celsius))
+                .collect::<Vec<_>>()
+                .join(", "),
+            None => UNKNOWN.to_string(),
+        };
+        Some(text)
+    }
+
+    /// One line per sensor, e.g. "Temperature (CPU)  54°C Tctl (high 80°C, crit 100°C)"
+    fn lines(&self, info: &SystemInfo, messages: &'static Messages, _settings: &Settings) -> Vec<Line> {
+        let Some(sensors) = &info.temperatures else {
+            return vec![Line {
+                label: messages.temperature.to_string(),
+                text: UNKNOWN.to_string(),
+                gauge: None,
+            }];
+        };
+
+        sensors
+            .iter()
+            .map(|sensor| {
+                let name = sensor_name(sensor);
+                // two NVMe drives or GPUs are told apart by their device
+                let twins = sensors.iter().filter(|other| sensor_name(other) == name).count();
+                let label = match &sensor.device {
+                    Some(device) if twins > 1 => format!("{} ({} {})", messages.temperature, name, device),
+                    _ => format!("{} ({})", messages.temperature, name),
+                };
+                Line {
+                    label,
+                    text: format_sensor(sensor, messages),
+                    gauge: None,
+                }
+            })
+            .collect()
+    }
+}
+
+pub struct ResolutionModule;
+
+impl Module for ResolutionModule {
+    fn name(&self) -> &'static str {
+        "resolution"
+    }
+
+    fn label(&self, messages: &'static Messages) -> &'static str {
+        messages.resolution
+    }
+
+    fn collect(&self, _settings: &Settings) -> Result<Update, Error> {
+        let displays = crate::get_displays()?;
+        Ok(Box::new(move |info| info.displays = Some(displays)))
+    }
+
+    /// Every monitor on one line, e.g. "2560x1440 @ 144 Hz, 1920x1080 @ 60 Hz"
+    fn render(&self, info: &SystemInfo, messages: &Messages, _settings: &Settings) -> Option<String> {
+        let text = match &info.displays {
+            Some(displays) => displays
+                .iter()
+                .filter_map(|display| format_mode(display, messages))
+                .collect::<Vec<_>>()
+                .join(", "),
+            None => UNKNOWN.to_string(),
+        };
+        Some(text)
+    }
+
+    /// One line per monitor, named after its connector when there are several
+    fn lines(&self, info: &SystemInfo, messages: &'static Messages, _settings: &Settings) -> Vec<Line> {
+        let Some(displays) = &info.displays else {
+            return vec![Line {
+                label: messages.resolution.to_string(),
+                text: UNKNOWN.to_string(),
+                gauge: None,
+            }];
+        };
+
+        displays
+            .iter()
+            .map(|display| Line {
+                label: if displays.len() > 1 {
+                    format!("{} ({})", messages.resolution, display.connector)
+                } else {
+                    messages.resolution.to_string()
+                },
+                text: format_display(display, messages),
+                gauge: None,
+            })
+            .collect()
+    }
+}
+
+pub struct NetworkModule;
+
+impl Module for NetworkModule {
+    fn name(&self) -> &'static str {
+        "network"
+    }
+
+    fn label(&self, messages: &'static Messages) -> &'static str {
+        messages.network
+    }
+
+    fn collect(&self, _settings: &Settings) -> Result<Update, Error> {
+        let interfaces = crate::get_interfaces()?;
+        Ok(Box::new(move |info| info.network = Some(interfaces)))
+    }
+
+    /// The default-route interface, e.g. "wlp3s0 (192.168.1.10)"
+    fn render(&self, info: &SystemInfo, messages: &Messages, _settings: &Settings) -> Option<String> {
+        let text = match &info.network {
+            Some(interfaces) => match interfaces.iter().find(|interface| interface.default_route) {
+                Some(interface) => match interface.addresses().next() {
+                    Some(address) => format!("{} ({})", interface.name, address.ip),
+                    None => interface.name.clone(),
+                },
+                None => messages.no_connection.to_string(),
+            },
+            None => UNKNOWN.to_string(),
+        };
+        Some(text)
+    }
+
+    /// One line per connected interface; being offline is a valid answer, not a failure
+    fn lines(&self, info: &SystemInfo, messages: &'static Messages, settings: &Settings) -> Vec<Line> {
+        let connected: Vec<&crate::Interface> = info
+            .network
+            .iter()
+            .flatten()
+            .filter(|interface| {
+                interface.default_route
+                    || (interface.state != LinkState::Down && interface.addresses().next().is_some())
+            })
+            .collect();
+
+        if connected.is_empty() {
+            return vec![Line {
+                label: messages.network.to_string(),
+                text: self.render(info, messages, settings).unwrap_or_default(),
+                gauge: None,
+            }];
+        }
+        connected
+            .into_iter()
+            .map(|interface| Line {
+                label: format!("{} ({})", messages.network, interface.name),
+                text: format_interface(interface, messages),
+                gauge: None,
+            })
+            .collect()
+    }
+}
+
+pub struct BatteryModule;
+
+impl Module for BatteryModule {
+    fn name(&self) -> &'static str {
+        "battery"
+    }
+
+    fn label(&self, messages: &'static Messages) -> &'static str {
+        messages.battery
+    }
+
+    /// Desktops have no battery, so its absence is not a failure
+    fn collect(&self, _settings: &Settings) -> Result<Update, Error> {
+        let power = crate::get_power()?;
+        Ok(Box::new(move |info| info.power = power))
+    }
+
+    /// Every battery on one line; desktops without one skip the line instead of showing "?"
+    fn render(&self, info: &SystemInfo, messages: &Messages, _settings: &Settings) -> Option<String> {
+        let power = info.power.as_ref().filter(|power| !power.batteries.is_empty())?;
+        let batteries: Vec<String> = power
+            .batteries
+            .iter()
+            .map(|battery| format!("{} {}", battery.display_name(), format_battery(battery, messages)))
+            .collect();
+        Some(batteries.join("; "))
+    }
+
+    /// The charger, then one line per battery, named when there are several
+    fn lines(&self, info: &SystemInfo, messages: &'static Messages, _settings: &Settings) -> Vec<Line> {
+        let Some(power) = &info.power else {
+            return Vec::new();
+        };
+
+        let ac = power.ac_online.map(|online| Line {
+            label: messages.ac_adapter.to_string(),
+            text: if online { messages.ac_online } else { messages.ac_offline }.to_string(),
+            gauge: None,
+        });
+        let batteries = power.batteries.iter().map(|battery| Line {
+            label: if power.batteries.len() > 1 {
+                format!("{} ({})", messages.battery, battery.display_name())
+            } else {
+                messages.battery.to_string()
+            },
+            text: format_battery(battery, messages),
+            gauge: Some(Gauge::charge(battery.capacity as f64)),
+        });
+        ac.into_iter().chain(batteries).collect()
+    }
+}
+
+static MODULES: &[&dyn Module] = &[
+    &UsernameModule,
+    &HostnameModule,
+    &DistroModule,
+    &KernelModule,
+    &UptimeModule,
+    &PackagesModule,
+    &ShellModule,
+    &HostModule,
+    &BiosModule,
+    &BoardModule,
+    &CpuModule,
+    &CpuCacheModule,
+    &GpuModule,
+    &MemoryModule,
+    &SwapModule,
+    &DiskModule,
+    &CpuUsageModule,
+    &TemperatureModule,
+    &LoadModule,
+    &DeModule,
+    &WmModule,
+    &ResolutionModule,
+    &TerminalModule,
+    &LocaleModule,
+    &ProcessesModule,
+    &UsersModule,
+    &NetworkModule,
+    &BatteryModule,
+];
+
+/// Every built-in module, in the order `--json` and `--timings` list them
+pub fn all() -> &'static [&'static dyn Module] {
+    MODULES
+}
+
+pub fn find(name: &str) -> Option<&'static dyn Module> {
+    MODULES.iter().copied().find(|module| module.name() == name)
+}
+
+/// "1.50 GiB", scaled to the largest unit that keeps the number at least 1
+pub fn format_size(bytes: u64, settings: &Settings) -> String {
+    let (value, unit) = scale(bytes, bytes, settings.units);
+    format!("{:.*} {}", settings.precision, value, unit)
+}
+
+/// `bytes` in the unit that suits `reference`, so "used / total" share one unit
+fn scale(bytes: u64, reference: u64, units: Units) -> (f64, &'static str) {
+    let (base, names) = match units {
+        Units::Binary => (1024.0, ["B", "KiB", "MiB", "GiB", "TiB", "PiB"]),
+        Units::Decimal => (1000.0, ["B", "KB", "MB", "GB", "TB", "PB"]),
+    };
+
+    let mut power = 0;
+    let mut reference = reference as f64;
+    while reference >= base && power + 1 < names.len() {
+        reference /= base;
+        power += 1;
+    }
+    (bytes as f64 / f64::powi(base, power as i32), names[power])
+}
+
+/// "3.12 GiB / 15.54 GiB (20%)"
+pub fn format_usage(usage: &Usage, settings: &Settings) -> String {
+    let (used, unit) = scale(usage.used, usage.total, settings.units);
+    let (total, _) = scale(usage.total, usage.total, settings.units);
+    format!(
+        "{:.*} {unit} / {:.*} {unit} ({}%)",
+        settings.precision,
+        used,
+        settings.precision,
+        total,
+        usage.percent(),
+        unit = unit
+    )
+}
+
+/// e.g. "1920x1200 @ 60 Hz", or "1920x1200 @ 60 Hz (native)" when the mode in use is unknown
+pub fn format_mode(display: &crate::Display, messages: &Messages) -> Option<String> {
+    let mut text = format!("{}x{}", display.width?, display.height?);
+    if let Some(refresh) = display.refresh {
+        text.push_str(&format!(" @ {:.0} Hz", refresh));
+    }
+    if !display.current {
+        text.push_str(&format!(" ({})", messages.native_mode));
+    }
+    Some(text)
+}
+
+/// e.g. "1920x1200 @ 60 Hz - DELL U2415 - 24.1\""
+pub fn format_display(display: &crate::Display, messages: &Messages) -> String {
+    let mut parts: Vec<String> = format_mode(display, messages).into_iter().collect();
+
+    if let Some(edid) = &display.edid {
+        let manufacturer = edid.manufacturer();
+        // names like "DELL U2415" already start with the brand
+        let name = match &edid.name {
+            Some(name) if name.to_lowercase().starts_with(&manufacturer.to_lowercase()) => name.clone(),
+            Some(name) => format!("{} {}", manufacturer, name),
+            None => manufacturer.to_string(),
+        };
+        parts.push(name);
+    }
+    if let Some(diagonal) = display.diagonal() {
+        parts.push(format!("{:.1}\"", diagonal));
+    }
+
+    if parts.is_empty() {
+        return display.connector.clone();
+    }
+    parts.join(" - ")
+}
+
+/// "CPU", "GPU" and "NVMe" for known kinds, the chip name for the rest
+pub fn sensor_name(sensor: &crate::Sensor) -> &str {
+    match sensor.kind {
+        crate::SensorKind::Cpu => "CPU",
+        crate::SensorKind::Gpu => "GPU",
+        crate::SensorKind::Nvme => "NVMe",
+        crate::SensorKind::Other => &sensor.chip,
+    }
+}
+
+/// e.g. "54°C Tctl (high 80°C, crit 100°C)"
+pub fn format_sensor(sensor: &crate::Sensor, messages: &Messages) -> String {
+    let mut text = format!("{:.0}°C", sensor.celsius);
+    if let Some(label) = &sensor.label {
+        text.push(' ');
+        text.push_str(label);
+    }
+
+    let limits: Vec<String> = [
+        (sensor.high, messages.temperature_high),
+        (sensor.critical, messages.temperature_critical),
+    ]
+    .into_iter()
+    .filter_map(|(limit, format)| Some(format.replace("{t}", &format!("{:.0}°C", limit?))))
+    .collect();
+    if !limits.is_empty() {
+        text.push_str(&format!(" ({})", limits.join(", ")));
+    }
+    text
+}
+
+/// Firmware version and date, e.g. "N32ET86W (1.62) (06/15/2023)"
+pub fn format_bios(host: &crate::Host) -> String {
+    match &host.bios {
+        Some(crate::Bios { version, date: Some(date) }) => format!("{} ({})", version, date),
+        Some(bios) => bios.version.clone(),
+        None => UNKNOWN.to_string(),
+    }
+}
+
+/// e.g. "zsh 5.9" or "GNOME 46.0"
+pub fn with_version(name: &str, version: Option<&str>) -> String {
+    match version {
+        Some(version) => format!("{} {}", name, version),
+        None => name.to_string(),
+    }
+}
+
+/// e.g. "Hyprland (Wayland)", or just "TTY" on a console
+pub fn format_window_manager(wm: &crate::WindowManager) -> String {
+    match (&wm.name, wm.session) {
+        (Some(name), Some(session)) => format!("{} ({})", name, session.name()),
+        (Some(name), None) => name.clone(),
+        (None, Some(session)) => session.name().to_string(),
+        (None, None) => UNKNOWN.to_string(),
+    }
+}
+
+/// The emulator with any multiplexer inside it, e.g. "kitty (tmux)"
+pub fn format_terminal(terminal: &crate::Terminal) -> String {
+    match &terminal.multiplexer {
+        Some(multiplexer) => format!("{} ({})", terminal.name, multiplexer),
+        None => terminal.name.clone(),
+    }
+}
+
+/// e.g. "192.168.1.10/24, 2a02:6b8::1/64 - 1000 Mbit/s - 3c:7c:3f:1a:2b:4c [default]"
+pub fn format_interface(interface: &crate::Interface, messages: &Messages) -> String {
+    let addresses: Vec<String> = interface.addresses().map(|address| address.to_string()).collect();
+    let parts: Vec<String> = [
+        (!addresses.is_empty()).then(|| addresses.join(", ")),
+        interface.speed.map(|speed| messages.link_speed.replace("{n}", &speed.to_string())),
+        interface.mac.clone(),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    let mut text = parts.join(" - ");
+    if interface.default_route {
+        text.push_str(&format!(" [{}]", messages.default_route));
+    }
+    text
+}
+
+/// Usage and filesystem of one mount, e.g. "120.50 GiB / 450.00 GiB (26%) - btrfs"
+pub fn format_disk(disk: &crate::Disk, settings: &Settings) -> String {
+    format!("{} - {}", format_usage(&disk.usage, settings), disk.fstype)
+}
+
+/// Usage plus how well zram compresses, e.g. "1.00 GiB / 8.00 GiB (12%), zram 1.00 GiB → 0.25 GiB"
+pub fn format_swap(swap: &crate::Swap, messages: &Messages, settings: &Settings) -> String {
+    if swap.total == 0 {
+        return messages.swap_disabled.to_string();
+    }
+
+    let mut text = format_usage(&swap.usage(), settings);
+    if let Some(zram) = swap.zram.filter(|zram| zram.stored > 0) {
+        text.push_str(&format!(
+            ", zram {} → {}",
+            format_size(zram.stored, settings),
+            format_size(zram.compressed, settings)
+        ));
+    }
+    text
+}
+
+/// "1423 (dpkg), 34 (flatpak)"
+pub fn format_packages(counts: &[crate::PackageCount]) -> String {
+    counts
+        .iter()
+        .map(|package| format!("{} ({})", package.count, package.manager))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// "Intel UHD Graphics 620 [display], NVIDIA GeForce MX150"; the marker only appears with several GPUs
+pub fn format_gpus(gpus: &[crate::Gpu], messages: &Messages) -> String {
+    gpus.iter()
+        .map(|gpu| {
+            let mut text = format!("{} {}", gpu.vendor, gpu.name);
+            if gpu.display && gpus.len() > 1 {
+                text.push_str(&format!(" [{}]", messages.gpu_display));
+            }
+            text
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// "2× Intel Xeon E5-2690 v4 28C/56T @ 1.20/3.50 GHz"
+pub fn format_cpu(cpu: &crate::Cpu) -> String {
+    let mut text = String::new();
+    if cpu.sockets > 1 {
+        text.push_str(&format!("{}× ", cpu.sockets));
+    }
+    text.push_str(&format!("{} {}C/{}T", cpu.model, cpu.cores, cpu.threads));
+
+    let ghz = |mhz: u32| format!("{:.2}", mhz as f64 / 1000.0);
+    match (cpu.current_frequency, cpu.max_frequency) {
+        (Some(current), Some(max)) if current != max => {
+            text.push_str(&format!(" @ {}/{} GHz", ghz(current), ghz(max)))
+        }
+        (_, Some(mhz)) | (Some(mhz), None) => text.push_str(&format!(" @ {} GHz", ghz(mhz))),
+        (None, None) => {}
+    }
+    text
+}
+
+/// "L1d 4×32 KiB, L1i 4×32 KiB, L2 4×256 KiB, L3 8 MiB"
+pub fn format_caches(caches: &[crate::Cache]) -> String {
+    if caches.is_empty() {
+        return UNKNOWN.to_string();
+    }
+
+    caches
+        .iter()
+        .map(|cache| {
+            let suffix = match cache.kind {
+                crate::CacheKind::Data => "d",
+                crate::CacheKind::Instruction => "i",
+                crate::CacheKind::Unified => "",
+            };
+            // 1.25 MiB reads as 1280 KiB; only whole mebibytes are shown as such
+            let size = if cache.size >= 1024 * 1024 && cache.size % (1024 * 1024) == 0 {
+                format!("{} MiB", cache.size / (1024 * 1024))
+            } else {
+                format!("{} KiB", cache.size / 1024)
+            };
+            match cache.count {
+                1 => format!("L{}{} {}", cache.level, suffix, size),
+                count => format!("L{}{} {}×{}", cache.level, suffix, count, size),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// "12%", or "12% (3% 40% 5% 1%)" with `per_core`
+pub fn format_cpu_usage(usage: &crate::CpuUsage, per_core: bool) -> String {
+    let mut text = format!("{:.0}%", usage.percent());
+    if per_core && !usage.cores.is_empty() {
+        let cores: Vec<String> = usage
+            .core_percents()
+            .iter()
+            .map(|percent| format!("{:.0}%", percent))
+            .collect();
+        text.push_str(&format!(" ({})", cores.join(" ")));
+    }
+    text
+}
+
+pub fn format_uptime(uptime: Duration, messages: &Messages) -> String {
+    let secs = uptime.as_secs();
+    messages.format_uptime(secs / 86400, (secs % 86400) / 3600, (secs % 3600) / 60)
+}
+
+/// e.g. "87% [Discharging], 2h 15m left, health 91%, 312 cycles"
+pub fn format_battery(battery: &Battery, messages: &Messages) -> String {
+    let status = match battery.status {
+        BatteryStatus::Charging => messages.charging,
+        BatteryStatus::Discharging => messages.discharging,
+        BatteryStatus::Full => messages.full,
+        BatteryStatus::NotCharging => messages.not_charging,
+        BatteryStatus::Unknown => messages.unknown_status,
+    };
+    let mut text = format!("{}% [{}]", battery.capacity, status);
+
+    let remaining = [
+        (battery.time_to_empty, messages.time_to_empty),
+        (battery.time_to_full, messages.time_to_full),
+    ];
+    for (time, format) in remaining {
+        if let Some(time) = time {
+            let minutes = time.as_secs() / 60;
+            text.push_str(", ");
+            text.push_str(
+                &format
+                    .replace("{h}", &(minutes / 60).to_string())
+                    .replace("{m}", &(minutes % 60).to_string()),
+            );
+        }
+    }
+    if let Some(health) = battery.health {
+        text.push_str(", ");
+        text.push_str(&messages.battery_health.replace("{p}", &format!("{:.0}", health)));
+    }
+    if let Some(cycles) = battery.cycle_count {
+        text.push_str(", ");
+        text.push_str(&messages.battery_cycles.replace("{n}", &cycles.to_string()));
+    }
+    text
+}
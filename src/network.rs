@@ -0,0 +1,286 @@
+//! Network interfaces from `/sys/class/net`, `getifaddrs(3)` and the kernel routing tables
+
+use crate::error::{Error, Result, read, read_dir};
+use std::ffi::CStr;
+use std::fmt;
+use std::fs;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::Path;
+
+const NET: &str = "/sys/class/net";
+
+/// `IFF_LOOPBACK` from `<net/if.h>`
+const IFF_LOOPBACK: u32 = 0x8;
+/// `RTF_UP` and `RTF_REJECT` from `<linux/route.h>`
+const RTF_UP: u32 = 0x1;
+const RTF_REJECT: u32 = 0x200;
+
+/// `operstate` from sysfs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkState {
+    Up,
+    Down,
+    /// Waiting for something like 802.1X authentication
+    Dormant,
+    /// Tunnels and other drivers that do not track carrier state
+    Unknown,
+}
+
+impl LinkState {
+    fn from_sysfs(state: &str) -> LinkState {
+        match state {
+            "up" => LinkState::Up,
+            "down" | "lowerlayerdown" | "notpresent" => LinkState::Down,
+            "dormant" => LinkState::Dormant,
+            _ => LinkState::Unknown,
+        }
+    }
+}
+
+/// An address with its prefix length, shown as `192.168.1.10/24`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Address {
+    pub ip: IpAddr,
+    pub prefix: u8,
+}
+
+impl Address {
+    /// Scoped to the link, like `fe80::/10`; every IPv6 interface has one
+    pub fn is_link_local(&self) -> bool {
+        match self.ip {
+            IpAddr::V4(ip) => ip.is_link_local(),
+            IpAddr::V6(ip) => ip.segments()[0] & 0xffc0 == 0xfe80,
+        }
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.ip, self.prefix)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Interface {
+    /// e.g. "wlp3s0"
+    pub name: String,
+    pub state: LinkState,
+    /// Hardware address; `None` for tunnels and other interfaces without one
+    pub mac: Option<String>,
+    pub ipv4: Vec<Address>,
+    pub ipv6: Vec<Address>,
+    /// Negotiated link speed in Mbit/s, when the driver knows it
+    pub speed: Option<u32>,
+    /// Carries the IPv4 or IPv6 default route, i.e. the way to the internet
+    pub default_route: bool,
+}
+
+impl Interface {
+    /// IPv4 first, then IPv6, link-local ones left out
+    pub fn addresses(&self) -> impl Iterator<Item = &Address> {
+        self.ipv4
+            .iter()
+            .chain(&self.ipv6)
+            .filter(|address| !address.is_link_local())
+    }
+}
+
+fn attribute(dir: &Path, name: &str) -> Option<String> {
+    let value = fs::read_to_string(dir.join(name)).ok()?;
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// Interfaces with an IPv4 default route, from `/proc/net/route`:
+/// `Iface Destination Gateway Flags RefCnt Use Metric Mask ...` after a header line
+fn ipv4_default_routes(routes: &str) -> impl Iterator<Item = &str> {
+    routes.lines().skip(1).filter_map(|line| {
+        let fields: Vec<_> = line.split_whitespace().collect();
+        let flags = u32::from_str_radix(fields.get(3)?, 16).ok()?;
+        match fields[..] {
+            [iface, "00000000", _, _, _, _, _, "00000000", ..] if flags & RTF_UP != 0 => {
+                Some(iface)
+            }
+            _ => None,
+        }
+    })
+}
+
+/// Interfaces with an IPv6 default route, from `/proc/net/ipv6_route`:
+/// `dest dest_len src src_len gateway metric refcnt use flags iface`, no header
+fn ipv6_default_routes(routes: &str) -> impl Iterator<Item = &str> {
+    routes.lines().filter_map(|line| {
+        let fields: Vec<_> = line.split_whitespace().collect();
+        let [dest, "00", _, _, _, _, _, _, flags, iface] = fields[..] else {
+            return None;
+        };
+        let flags = u32::from_str_radix(flags, 16).unwrap_or(0);
+        // the kernel keeps an unreachable default route on `lo`
+        (dest.bytes().all(|digit| digit == b'0') && flags & RTF_UP != 0 && flags & RTF_REJECT == 0)
+            .then_some(iface)
+    })
+}
+
+/// Interfaces holding a default route in `/proc/net/route` or `/proc/net/ipv6_route`
+fn default_route_interfaces() -> Vec<String> {
+    let ipv4 = read("/proc/net/route").unwrap_or_default();
+    let ipv6 = read("/proc/net/ipv6_route").unwrap_or_default();
+    ipv4_default_routes(&ipv4)
+        .chain(ipv6_default_routes(&ipv6))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Netmask to prefix length, e.g. 255.255.255.0 to 24
+fn prefix(mask: &[u8]) -> u8 {
+    mask.iter().map(|byte| byte.count_ones() as u8).sum()
+}
+
+/// `(interface, address)` pairs for every IPv4 and IPv6 address, through the same netlink dump `ip addr` uses
+fn addresses() -> Result<Vec<(String, Address)>> {
+    let mut list: *mut libc::ifaddrs = std::ptr::null_mut();
+    if unsafe { libc::getifaddrs(&mut list) } != 0 {
+        return Err(Error::Sys(format!(
+            "getifaddrs: {}",
+            std::io::Error::last_os_error()
+        )));
+    }
+
+    let mut addresses = Vec::new();
+    let mut entry = list;
+    while !entry.is_null() {
+        let ifaddr = unsafe { &*entry };
+        entry = ifaddr.ifa_next;
+        if ifaddr.ifa_addr.is_null() || ifaddr.ifa_netmask.is_null() {
+            continue;
+        }
+
+        let name = unsafe { CStr::from_ptr(ifaddr.ifa_name) }
+            .to_string_lossy()
+            .into_owned();
+        let address = match unsafe { (*ifaddr.ifa_addr).sa_family } as libc::c_int {
+            libc::AF_INET => {
+                let (addr, mask) = unsafe {
+                    (
+                        &*(ifaddr.ifa_addr as *const libc::sockaddr_in),
+                        &*(ifaddr.ifa_netmask as *const libc::sockaddr_in),
+                    )
+                };
+                Address {
+                    // s_addr is in network byte order, as the octets are in memory
+                    ip: IpAddr::V4(Ipv4Addr::from(addr.sin_addr.s_addr.to_ne_bytes())),
+                    prefix: prefix(&mask.sin_addr.s_addr.to_ne_bytes()),
+                }
+            }
+            libc::AF_INET6 => {
+                let (addr, mask) = unsafe {
+                    (
+                        &*(ifaddr.ifa_addr as *const libc::sockaddr_in6),
+                        &*(ifaddr.ifa_netmask as *const libc::sockaddr_in6),
+                    )
+                };
+                Address {
+                    ip: IpAddr::V6(Ipv6Addr::from(addr.sin6_addr.s6_addr)),
+                    prefix: prefix(&mask.sin6_addr.s6_addr),
+                }
+            }
+            _ => continue,
+        };
+        addresses.push((name, address));
+    }
+
+    unsafe { libc::freeifaddrs(list) };
+    Ok(addresses)
+}
+
+/// Every interface but loopback, in name order
+pub fn get_interfaces() -> Result<Vec<Interface>> {
+    let mut paths: Vec<_> = read_dir(NET)?.flatten().map(|entry| entry.path()).collect();
+    paths.sort();
+
+    let addresses = addresses()?;
+    let defaults = default_route_interfaces();
+
+    let interfaces = paths
+        .iter()
+        .filter_map(|path| {
+            let name = path.file_name()?.to_string_lossy().into_owned();
+            let flags = attribute(path, "flags")
+                .and_then(|flags| u32::from_str_radix(flags.trim_start_matches("0x"), 16).ok())
+                .unwrap_or(0);
+            if flags & IFF_LOOPBACK != 0 {
+                return None;
+            }
+
+            let of_family = |v4: bool| -> Vec<Address> {
+                addresses
+                    .iter()
+                    .filter(|(owner, address)| *owner == name && address.ip.is_ipv4() == v4)
+                    .map(|(_, address)| *address)
+                    .collect()
+            };
+
+            Some(Interface {
+                state: attribute(path, "operstate")
+                    .map(|state| LinkState::from_sysfs(&state))
+                    .unwrap_or(LinkState::Unknown),
+                mac: attribute(path, "address")
+                    .filter(|mac| mac.bytes().any(|c| c != b'0' && c != b':')),
+                ipv4: of_family(true),
+                ipv6: of_family(false),
+                // unreadable while the link is down, -1 when the driver cannot tell
+                speed: attribute(path, "speed")
+                    .and_then(|speed| speed.parse::<i64>().ok())
+                    .filter(|&speed| speed > 0)
+                    .map(|speed| speed as u32),
+                default_route: defaults.contains(&name),
+                name,
+            })
+        })
+        .collect();
+
+    Ok(interfaces)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROUTE: &str = "\
+Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT
+wlan0\t00000000\t0101A8C0\t0003\t0\t0\t600\t00000000\t0\t0\t0
+wlan0\t0001A8C0\t00000000\t0001\t0\t0\t600\t00FFFFFF\t0\t0\t0
+tun0\t00000000\t00000000\t0000\t0\t0\t50\t00000000\t0\t0\t0
+";
+
+    const IPV6_ROUTE: &str = "\
+00000000000000000000000000000000 00 00000000000000000000000000000000 00 fe800000000000000000000000000001 00000064 00000001 00000000 00000003 wlan0
+fe800000000000000000000000000000 40 00000000000000000000000000000000 00 00000000000000000000000000000000 00000100 00000001 00000000 00000001 wlan0
+00000000000000000000000000000000 00 00000000000000000000000000000000 00 00000000000000000000000000000000 ffffffff 00000001 00000000 00200200 lo
+";
+
+    #[test]
+    fn ipv4_default_route_needs_zero_destination_and_mask_and_the_up_flag() {
+        assert_eq!(ipv4_default_routes(ROUTE).collect::<Vec<_>>(), ["wlan0"]);
+        assert_eq!(ipv4_default_routes("").count(), 0);
+    }
+
+    #[test]
+    fn ipv6_default_route_skips_the_unreachable_one_on_lo() {
+        assert_eq!(
+            ipv6_default_routes(IPV6_ROUTE).collect::<Vec<_>>(),
+            ["wlan0"]
+        );
+    }
+
+    #[test]
+    fn prefix_counts_bits_in_network_byte_order() {
+        assert_eq!(prefix(&[255, 255, 255, 0]), 24);
+        assert_eq!(prefix(&[255, 255, 240, 0]), 20);
+        assert_eq!(prefix(&0xffff_f000u32.to_be_bytes()), 20);
+        assert_eq!(prefix(&[0; 4]), 0);
+        let mut ipv6 = [0u8; 16];
+        ipv6[..8].fill(0xff);
+        assert_eq!(prefix(&ipv6), 64);
+    }
+}
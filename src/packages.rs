@@ -0,0 +1,247 @@
+//! Installed package counts, read straight from each package manager's database
+
+use crate::error::{Error, Result, read, read_dir, run};
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+mod rpmdb;
+
+pub use rpmdb::count_rpmdb;
+
+/// Packages installed through one package manager
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageCount {
+    /// Package manager name, e.g. "dpkg" or "flatpak"
+    pub manager: &'static str,
+    pub count: usize,
+}
+
+/// A manager whose database is missing fails; one that is present but empty counts zero
+type Counter = fn() -> Result<usize>;
+
+static MANAGERS: &[(&str, Counter)] = &[
+    ("dpkg", count_dpkg),
+    ("pacman", count_pacman),
+    ("rpm", count_rpm),
+    ("apk", count_apk),
+    ("xbps", count_xbps),
+    ("portage", count_portage),
+    ("nix-system", || count_nix("/run/current-system")),
+    ("nix-default", || count_nix("/nix/var/nix/profiles/default")),
+    ("nix-user", || count_nix(home()?.join(".nix-profile"))),
+    ("flatpak", count_flatpak),
+    ("snap", count_snap),
+    ("cargo", count_cargo),
+];
+
+/// A manager that is simply not installed here, as opposed to one whose database is unreadable
+fn is_absent(err: &Error) -> bool {
+    match err {
+        Error::Io { kind, .. } => *kind == io::ErrorKind::NotFound,
+        Error::Command { .. } | Error::Env(_) | Error::NotFound(_) => true,
+        Error::Parse { .. } | Error::Sys(_) => false,
+    }
+}
+
+/// Every package manager that has something installed, in a fixed order.
+/// A database that is there but cannot be read fails the whole count rather than leaving a
+/// manager out of it without a word
+pub fn get_package_count() -> Result<Vec<PackageCount>> {
+    let mut counts = Vec::new();
+    for &(manager, count) in MANAGERS {
+        match count() {
+            Ok(0) => {}
+            Ok(count) => counts.push(PackageCount { manager, count }),
+            Err(err) if is_absent(&err) => {}
+            Err(err) => return Err(err),
+        }
+    }
+
+    if counts.is_empty() {
+        return Err(Error::NotFound("package manager database"));
+    }
+    Ok(counts)
+}
+
+fn home() -> Result<PathBuf> {
+    crate::error::env_var(&["HOME"]).map(PathBuf::from)
+}
+
+/// Directories directly inside `path`
+fn count_dirs(path: impl AsRef<Path>) -> Result<usize> {
+    Ok(read_dir(path)?
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .count())
+}
+
+/// Only stanzas with `Status: install ok installed`; removed packages keep their config stanza
+fn count_dpkg() -> Result<usize> {
+    let status = read("/var/lib/dpkg/status")?;
+    Ok(status
+        .lines()
+        .filter(|line| line.starts_with("Status:") && line.ends_with(" installed"))
+        .count())
+}
+
+fn count_pacman() -> Result<usize> {
+    count_dirs("/var/lib/pacman/local")
+}
+
+/// Fedora 36+ keeps the database in `/usr/lib/sysimage/rpm`, `/var/lib/rpm` is a symlink to it
+fn count_rpm() -> Result<usize> {
+    match count_rpmdb("/usr/lib/sysimage/rpm") {
+        Err(err) if is_absent(&err) => count_rpmdb("/var/lib/rpm"),
+        counted => counted,
+    }
+}
+
+fn count_apk() -> Result<usize> {
+    let installed = read("/lib/apk/db/installed")?;
+    Ok(installed
+        .lines()
+        .filter(|line| line.starts_with("P:"))
+        .count())
+}
+
+/// `pkgdb-0.38.plist` stores `<key>state</key><string>installed</string>` for every package
+fn count_xbps() -> Result<usize> {
+    let pkgdb = read_dir("/var/db/xbps")?
+        .flatten()
+        .map(|entry| entry.path())
+        .find(|path| {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            name.starts_with("pkgdb-") && name.ends_with(".plist")
+        })
+        .ok_or(Error::NotFound("pkgdb-*.plist in /var/db/xbps"))?;
+
+    Ok(read(pkgdb)?
+        .lines()
+        .filter(|line| line.trim() == "<string>installed</string>")
+        .count())
+}
+
+/// `/var/db/pkg/<category>/<package>-<version>`
+fn count_portage() -> Result<usize> {
+    read_dir("/var/db/pkg")?
+        .flatten()
+        .map(|category| category.path())
+        .filter(|category| category.is_dir())
+        .map(count_dirs)
+        .sum()
+}
+
+/// Store paths the profile depends on, the way `nix-store` itself counts them
+fn count_nix(profile: impl AsRef<Path>) -> Result<usize> {
+    let profile = profile.as_ref();
+    if !profile.exists() {
+        return Err(Error::Io {
+            path: profile.to_path_buf(),
+            kind: std::io::ErrorKind::NotFound,
+        });
+    }
+
+    let requisites = run(
+        "nix-store",
+        &["--query", "--requisites", &profile.to_string_lossy()],
+    )?;
+    Ok(requisites
+        .lines()
+        .filter(|path| !path.ends_with(".drv"))
+        .count())
+}
+
+/// Apps and runtimes, system-wide and per user, one per installed branch
+fn count_flatpak() -> Result<usize> {
+    let mut roots = vec![PathBuf::from("/var/lib/flatpak")];
+    if let Ok(home) = home() {
+        roots.push(home.join(".local/share/flatpak"));
+    }
+
+    let mut found = false;
+    let mut count = 0;
+    for root in &roots {
+        for kind in ["app", "runtime"] {
+            let Ok(refs) = read_dir(root.join(kind)) else {
+                continue;
+            };
+            found = true;
+
+            // <kind>/<id>/<arch>/<branch>; `current` is a symlink to one of them
+            for arch in refs.flatten().flat_map(|id| fs::read_dir(id.path())) {
+                for arch in arch.flatten().filter(|arch| arch.file_name() != "current") {
+                    count += count_dirs(arch.path()).unwrap_or(0);
+                }
+            }
+        }
+    }
+
+    if !found {
+        return Err(Error::NotFound("flatpak installation"));
+    }
+    Ok(count)
+}
+
+/// Every mounted snap has `/snap/<name>/current`
+fn count_snap() -> Result<usize> {
+    Ok(read_dir("/snap")?
+        .flatten()
+        .filter(|entry| entry.path().join("current").exists())
+        .count())
+}
+
+/// Proxies rustup installs next to `cargo install`ed binaries
+const RUSTUP_PROXIES: &[&str] = &[
+    "cargo",
+    "cargo-clippy",
+    "cargo-fmt",
+    "cargo-miri",
+    "clippy-driver",
+    "rls",
+    "rust-analyzer",
+    "rust-gdb",
+    "rust-gdbgui",
+    "rust-lldb",
+    "rustc",
+    "rustdoc",
+    "rustfmt",
+    "rustup",
+];
+
+fn count_cargo() -> Result<usize> {
+    let cargo_home = env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .map_or_else(|| home().map(|home| home.join(".cargo")), Ok)?;
+
+    Ok(read_dir(cargo_home.join("bin"))?
+        .flatten()
+        .filter(|entry| {
+            let name = entry.file_name();
+            !RUSTUP_PROXIES.contains(&name.to_string_lossy().as_ref())
+        })
+        .count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_managers_are_absent() {
+        let missing = count_rpmdb("/nonexistent").unwrap_err();
+        assert!(is_absent(&missing), "{:?}", missing);
+        assert!(is_absent(&read("/nonexistent/status").unwrap_err()));
+        assert!(is_absent(&Error::Env(&["HOME"])));
+    }
+
+    #[test]
+    fn broken_databases_are_not_absent() {
+        assert!(!is_absent(&Error::parse("rpmdb.sqlite", "bad header")));
+        assert!(!is_absent(&Error::Io {
+            path: PathBuf::from("/var/lib/dpkg/status"),
+            kind: io::ErrorKind::PermissionDenied,
+        }));
+    }
+}
@@ -0,0 +1,429 @@
+//! Package count from the rpm database, read without `rpm` or any database library.
+//!
+//! rpm has used three on-disk formats over the years, newest first:
+//! `rpmdb.sqlite` (Fedora 33+, RHEL 9), `Packages.db` in rpm's own NDB format (openSUSE)
+//! and a Berkeley DB hash `Packages` (everything older). Each reader only walks the index
+//! structures and never touches the package headers themselves.
+
+use crate::error::{Error, Result};
+use std::fs::File;
+use std::os::unix::fs::FileExt;
+use std::path::{Path, PathBuf};
+
+/// Packages recorded in the rpm database inside `dir`, e.g. `/var/lib/rpm`
+pub fn count_rpmdb(dir: impl AsRef<Path>) -> Result<usize> {
+    let dir = dir.as_ref();
+
+    let sqlite = dir.join("rpmdb.sqlite");
+    if sqlite.exists() {
+        return count_sqlite(&sqlite);
+    }
+    let ndb = dir.join("Packages.db");
+    if ndb.exists() {
+        return count_ndb(&ndb);
+    }
+    let bdb = dir.join("Packages");
+    if bdb.exists() {
+        return count_bdb(&bdb);
+    }
+
+    Err(Error::NotFound("rpmdb.sqlite, Packages.db or Packages"))
+}
+
+/// A database file read one page at a time, so a 100 MB rpmdb costs a few reads
+struct Pages {
+    path: PathBuf,
+    file: File,
+    len: u64,
+}
+
+impl Pages {
+    fn open(path: &Path) -> Result<Pages> {
+        let file = File::open(path).map_err(|err| Error::io(path, err))?;
+        let len = file.metadata().map_err(|err| Error::io(path, err))?.len();
+        Ok(Pages {
+            path: path.to_path_buf(),
+            file,
+            len,
+        })
+    }
+
+    fn read(&self, offset: u64, len: usize) -> Result<Vec<u8>> {
+        if offset + len as u64 > self.len {
+            return Err(self.corrupt(format!("read past the end at offset {}", offset)));
+        }
+        let mut buf = vec![0; len];
+        self.file
+            .read_exact_at(&mut buf, offset)
+            .map_err(|err| Error::io(&self.path, err))?;
+        Ok(buf)
+    }
+
+    fn corrupt(&self, reason: impl Into<String>) -> Error {
+        Error::parse(self.path.display(), reason)
+    }
+}
+
+fn u16_be(bytes: &[u8], at: usize) -> usize {
+    u16::from_be_bytes([bytes[at], bytes[at + 1]]) as usize
+}
+
+fn u32_be(bytes: &[u8], at: usize) -> u32 {
+    u32::from_be_bytes(bytes[at..at + 4].try_into().unwrap())
+}
+
+fn u32_le(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+}
+
+/// SQLite variable-length integer: returns the value and its length in bytes
+fn varint(bytes: &[u8]) -> Option<(u64, usize)> {
+    let mut value = 0u64;
+    for (i, &byte) in bytes.iter().enumerate().take(9) {
+        if i == 8 {
+            return Some(((value << 8) | byte as u64, 9));
+        }
+        value = (value << 7) | (byte & 0x7f) as u64;
+        if byte & 0x80 == 0 {
+            return Some((value, i + 1));
+        }
+    }
+    None
+}
+
+const SQLITE_MAGIC: &[u8] = b"SQLite format 3\0";
+/// The smallest usable page size the file format allows, which keeps the payload limits positive
+const SQLITE_MIN_USABLE: usize = 480;
+const SQLITE_INTERIOR_TABLE: u8 = 5;
+const SQLITE_LEAF_TABLE: u8 = 13;
+
+/// A b-tree page of an SQLite file: its type, the offsets of its cells and the right-most child
+struct BtreePage {
+    data: Vec<u8>,
+    kind: u8,
+    cells: Vec<usize>,
+    right: Option<u32>,
+}
+
+struct Sqlite {
+    pages: Pages,
+    page_size: usize,
+    usable_size: usize,
+}
+
+impl Sqlite {
+    fn open(path: &Path) -> Result<Sqlite> {
+        let pages = Pages::open(path)?;
+        let header = pages.read(0, 100)?;
+        if &header[..16] != SQLITE_MAGIC {
+            return Err(pages.corrupt("not an SQLite database"));
+        }
+
+        let page_size = match u16_be(&header, 16) {
+            1 => 65536,
+            size => size,
+        };
+        if !page_size.is_power_of_two() || page_size < 512 {
+            return Err(pages.corrupt(format!("invalid page size {}", page_size)));
+        }
+        let usable_size = page_size - header[20] as usize;
+        if usable_size < SQLITE_MIN_USABLE {
+            return Err(pages.corrupt(format!(
+                "{} reserved bytes leave too little of a {}-byte page",
+                header[20], page_size
+            )));
+        }
+        Ok(Sqlite {
+            pages,
+            page_size,
+            usable_size,
+        })
+    }
+
+    /// Pages are numbered from 1; the first one starts with the 100-byte file header
+    fn btree_page(&self, number: u32) -> Result<BtreePage> {
+        if number == 0 {
+            return Err(self.pages.corrupt("b-tree points at page 0"));
+        }
+        let data = self
+            .pages
+            .read((number as u64 - 1) * self.page_size as u64, self.page_size)?;
+        let start = if number == 1 { 100 } else { 0 };
+
+        let kind = data[start];
+        let (header_len, right) = match kind {
+            SQLITE_LEAF_TABLE => (8, None),
+            SQLITE_INTERIOR_TABLE => (12, Some(u32_be(&data, start + 8))),
+            _ => {
+                return Err(self
+                    .pages
+                    .corrupt(format!("page {} is not a table b-tree page", number)));
+            }
+        };
+
+        let count = u16_be(&data, start + 3);
+        let cells_at = start + header_len;
+        if cells_at + count * 2 > data.len() {
+            return Err(self
+                .pages
+                .corrupt(format!("page {} has too many cells", number)));
+        }
+        let cells = (0..count)
+            .map(|i| u16_be(&data, cells_at + i * 2))
+            .collect();
+
+        Ok(BtreePage {
+            data,
+            kind,
+            cells,
+            right,
+        })
+    }
+
+    /// Visit every leaf page of the table b-tree rooted at `root`
+    fn for_each_leaf(
+        &self,
+        root: u32,
+        mut visit: impl FnMut(&BtreePage) -> Result<()>,
+    ) -> Result<()> {
+        let max_pages = self.pages.len / self.page_size as u64;
+        let mut stack = vec![root];
+        let mut visited = 0;
+
+        while let Some(number) = stack.pop() {
+            visited += 1;
+            if visited > max_pages {
+                return Err(self.pages.corrupt("b-tree has a loop"));
+            }
+
+            let page = self.btree_page(number)?;
+            if page.kind == SQLITE_LEAF_TABLE {
+                visit(&page)?;
+                continue;
+            }
+            for &cell in &page.cells {
+                if cell + 4 > page.data.len() {
+                    return Err(self.pages.corrupt(format!("cell out of page {}", number)));
+                }
+                stack.push(u32_be(&page.data, cell));
+            }
+            stack.extend(page.right);
+        }
+        Ok(())
+    }
+
+    /// Root page of `table`, looked up in `sqlite_master` (always rooted at page 1)
+    fn root_page(&self, table: &str) -> Result<u32> {
+        let mut root = None;
+        self.for_each_leaf(1, |page| {
+            for &cell in &page.cells {
+                let Some(cell) = page.data.get(cell..) else {
+                    continue;
+                };
+                if let Some((kind, name, page_number)) = self.master_row(cell)?
+                    && kind == "table"
+                    && name == table
+                {
+                    root = Some(page_number);
+                }
+            }
+            Ok(())
+        })?;
+
+        root.ok_or_else(|| self.pages.corrupt(format!("no `{}` table", table)))
+    }
+
+    /// How much of a table leaf cell's payload is stored on the page itself, the rest overflows
+    fn local_payload(&self, len: usize) -> usize {
+        let usable = self.usable_size;
+        let max_local = usable - 35;
+        if len <= max_local {
+            return len;
+        }
+        let min_local = (usable - 12) * 32 / 255 - 23;
+        let local = min_local + (len - min_local) % (usable - 4);
+        if local <= max_local { local } else { min_local }
+    }
+
+    /// The record in a table leaf cell: the serial types of its first `columns` columns and the
+    /// bytes after its header, as far as they fit in the local payload
+    fn record<'a>(&self, cell: &'a [u8], columns: usize) -> Option<(Vec<u64>, &'a [u8])> {
+        let (payload_len, len) = varint(cell)?;
+        let (_rowid, rowid_len) = varint(&cell[len..])?;
+        let payload = &cell[len + rowid_len..];
+        let payload = payload.get(..self.local_payload(payload_len as usize))?;
+
+        let (header_len, mut at) = varint(payload)?;
+        let mut types = Vec::new();
+        while at < header_len as usize && types.len() < columns {
+            let (serial, len) = varint(payload.get(at..)?)?;
+            types.push(serial);
+            at += len;
+        }
+        Some((types, payload.get(header_len as usize..)?))
+    }
+
+    /// `type`, `name` and `rootpage` of a `sqlite_master` row, if they fit in the local payload
+    fn master_row<'a>(&self, cell: &'a [u8]) -> Result<Option<(&'a str, &'a str, u32)>> {
+        let Some((types, mut body)) = self.record(cell, 4) else {
+            return Ok(None);
+        };
+        if let Some(serial) = types.iter().find(|serial| matches!(serial, 10 | 11)) {
+            return Err(self
+                .pages
+                .corrupt(format!("reserved serial type {} in sqlite_master", serial)));
+        }
+
+        let mut columns = Vec::new();
+        for serial in types {
+            let size = match serial {
+                0 | 8 | 9 => 0,
+                1..=4 => serial as usize,
+                5 => 6,
+                6 | 7 => 8,
+                _ => (serial as usize - 12) / 2,
+            };
+            let Some(bytes) = body.get(..size) else {
+                return Ok(None);
+            };
+            columns.push((serial, bytes));
+            body = &body[size..];
+        }
+
+        let text = |index: usize| {
+            let (serial, bytes) = columns.get(index)?;
+            (*serial >= 13 && serial % 2 == 1)
+                .then(|| std::str::from_utf8(bytes).ok())
+                .flatten()
+        };
+        let rootpage = match columns.get(3) {
+            Some((1..=4, bytes)) => bytes.iter().fold(0u32, |acc, &b| (acc << 8) | b as u32),
+            _ => return Ok(None),
+        };
+        Ok(text(0)
+            .zip(text(1))
+            .map(|(kind, name)| (kind, name, rootpage)))
+    }
+}
+
+/// Rows of the `Packages` table. Changes still sitting in `rpmdb.sqlite-wal` are not seen,
+/// but rpm checkpoints the log whenever it closes the database
+fn count_sqlite(path: &Path) -> Result<usize> {
+    let db = Sqlite::open(path)?;
+    let root = db.root_page("Packages")?;
+
+    let mut count = 0;
+    db.for_each_leaf(root, |page| {
+        count += page.cells.len();
+        Ok(())
+    })?;
+    Ok(count)
+}
+
+const NDB_MAGIC: u32 = u32::from_le_bytes(*b"RpmP");
+const NDB_SLOT_MAGIC: u32 = u32::from_le_bytes(*b"Slot");
+const NDB_PAGE_SIZE: usize = 4096;
+const NDB_HEADER_SIZE: usize = 32;
+const NDB_SLOT_SIZE: usize = 16;
+
+/// `Packages.db` starts with a slot table: 16-byte `Slot` records, one per package,
+/// with a zero block offset marking a free slot
+fn count_ndb(path: &Path) -> Result<usize> {
+    let pages = Pages::open(path)?;
+    let header = pages.read(0, NDB_HEADER_SIZE)?;
+    if u32_le(&header, 0) != NDB_MAGIC {
+        return Err(pages.corrupt("not an rpm NDB package database"));
+    }
+    if u32_le(&header, 4) != 0 {
+        return Err(pages.corrupt(format!("unsupported NDB version {}", u32_le(&header, 4))));
+    }
+
+    // the first slot page also holds the header, so there is always at least one
+    let slot_pages = u32_le(&header, 12) as usize;
+    if slot_pages == 0 {
+        return Err(pages.corrupt("no slot pages"));
+    }
+    let slots = pages.read(0, slot_pages * NDB_PAGE_SIZE)?;
+
+    let mut count = 0;
+    for slot in slots[NDB_HEADER_SIZE..].chunks_exact(NDB_SLOT_SIZE) {
+        if u32_le(slot, 0) != NDB_SLOT_MAGIC {
+            return Err(pages.corrupt("bad slot magic"));
+        }
+        if u32_le(slot, 8) != 0 {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+const BDB_HASH_MAGIC: u32 = 0x061561;
+const BDB_PAGE_HEADER_SIZE: usize = 26;
+const BDB_HASH_UNSORTED: u8 = 2;
+const BDB_HASH: u8 = 13;
+const BDB_KEYDATA: u8 = 1;
+
+/// Berkeley DB hash database keyed by the 4-byte package instance number.
+/// Key 0 holds rpm's bookkeeping, every other key is a package
+fn count_bdb(path: &Path) -> Result<usize> {
+    let pages = Pages::open(path)?;
+    let meta = pages.read(0, 512)?;
+
+    // the file uses the byte order of the machine that created it
+    let big_endian = match meta[12..16].try_into().unwrap() {
+        bytes if u32::from_le_bytes(bytes) == BDB_HASH_MAGIC => false,
+        bytes if u32::from_be_bytes(bytes) == BDB_HASH_MAGIC => true,
+        _ => return Err(pages.corrupt("not a Berkeley DB hash database")),
+    };
+    let u16_at = |bytes: &[u8], at: usize| {
+        let pair = [bytes[at], bytes[at + 1]];
+        (if big_endian {
+            u16::from_be_bytes(pair)
+        } else {
+            u16::from_le_bytes(pair)
+        }) as usize
+    };
+    let u32_at = |bytes: &[u8], at: usize| {
+        if big_endian {
+            u32_be(bytes, at)
+        } else {
+            u32_le(bytes, at)
+        }
+    };
+
+    let page_size = u32_at(&meta, 20) as usize;
+    if meta[24] != 0 {
+        return Err(pages.corrupt("encrypted Berkeley DB databases are not supported"));
+    }
+    if page_size < 512 {
+        return Err(pages.corrupt(format!("invalid page size {}", page_size)));
+    }
+    let last_page = u32_at(&meta, 32) as u64;
+
+    let mut count = 0;
+    for number in 1..=last_page {
+        let offset = number * page_size as u64;
+        let header = pages.read(offset, BDB_PAGE_HEADER_SIZE)?;
+        if !matches!(header[25], BDB_HASH_UNSORTED | BDB_HASH) {
+            continue;
+        }
+
+        let page = pages.read(offset, page_size)?;
+        let entries = u16_at(&page, 20);
+        if BDB_PAGE_HEADER_SIZE + entries * 2 > page_size {
+            return Err(pages.corrupt(format!("page {} has too many entries", number)));
+        }
+
+        // entries alternate key, data; a key item is a type byte followed by the key
+        for pair in 0..entries / 2 {
+            let key = u16_at(&page, BDB_PAGE_HEADER_SIZE + pair * 4);
+            let Some(item) = page.get(key..key + 5) else {
+                return Err(pages.corrupt(format!("entry out of page {}", number)));
+            };
+            if item[0] == BDB_KEYDATA && item[1..] != [0; 4] {
+                count += 1;
+            }
+        }
+    }
+    Ok(count)
+}
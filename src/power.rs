@@ -0,0 +1,284 @@
+//! Batteries and AC adapters from `/sys/class/power_supply`
+
+use crate::error::{Result, read_dir};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+const POWER_SUPPLY: &str = "/sys/class/power_supply";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatteryStatus {
+    Charging,
+    Discharging,
+    Full,
+    NotCharging,
+    Unknown,
+}
+
+impl BatteryStatus {
+    /// Parse the `status` attribute of a sysfs power supply
+    fn from_sysfs(status: &str) -> BatteryStatus {
+        match status {
+            "Charging" => BatteryStatus::Charging,
+            "Discharging" => BatteryStatus::Discharging,
+            "Full" => BatteryStatus::Full,
+            "Not charging" => BatteryStatus::NotCharging,
+            _ => BatteryStatus::Unknown,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Battery {
+    /// sysfs name, e.g. "BAT0" or "hidpp_battery_0"
+    pub name: String,
+    /// `model_name`, e.g. "G502 LIGHTSPEED" for a mouse
+    pub model: Option<String>,
+    /// Charge in percent
+    pub capacity: u8,
+    pub status: BatteryStatus,
+    /// Powers a mouse, headset or other device rather than the machine itself
+    pub peripheral: bool,
+    /// Only while discharging and only when the driver reports the current draw
+    pub time_to_empty: Option<Duration>,
+    /// Only while charging
+    pub time_to_full: Option<Duration>,
+    /// Full charge now against the design capacity, in percent
+    pub health: Option<f64>,
+    pub cycle_count: Option<u32>,
+}
+
+impl Battery {
+    /// The model for peripherals, the sysfs name for the machine's own batteries
+    pub fn display_name(&self) -> &str {
+        match &self.model {
+            Some(model) if self.peripheral => model,
+            _ => &self.name,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Power {
+    /// Whether the charger is plugged in; `None` without a mains or USB adapter in sysfs
+    pub ac_online: Option<bool>,
+    /// The machine's batteries first, then peripherals
+    pub batteries: Vec<Battery>,
+}
+
+fn attribute(supply: &Path, name: &str) -> Option<String> {
+    let value = fs::read_to_string(supply.join(name)).ok()?;
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// Micro-units (µWh, µA...); some drivers report the discharge current as negative
+fn number(supply: &Path, name: &str) -> Option<u64> {
+    attribute(supply, name)?
+        .parse::<i64>()
+        .ok()
+        .map(i64::unsigned_abs)
+}
+
+/// Charge left, capacities and draw, all from one unit family: energy in µWh drawn at µW, or
+/// charge in µAh drawn at µA. Ratios across the families would be off by the voltage
+struct Readings {
+    now: Option<u64>,
+    full: Option<u64>,
+    design: Option<u64>,
+    rate: Option<u64>,
+}
+
+/// Energy when the fuel gauge reports any of it, charge otherwise
+fn readings(supply: &Path) -> Readings {
+    let energy = ["energy_now", "energy_full", "energy_full_design"]
+        .iter()
+        .any(|name| number(supply, name).is_some());
+    if energy {
+        // drivers without `power_now` may still report current in µA and voltage in µV
+        let power = number(supply, "power_now").or_else(|| {
+            let current = number(supply, "current_now")? as u128;
+            let voltage = number(supply, "voltage_now")? as u128;
+            u64::try_from(current * voltage / 1_000_000).ok()
+        });
+        Readings {
+            now: number(supply, "energy_now"),
+            full: number(supply, "energy_full"),
+            design: number(supply, "energy_full_design"),
+            rate: power,
+        }
+    } else {
+        Readings {
+            now: number(supply, "charge_now"),
+            full: number(supply, "charge_full"),
+            design: number(supply, "charge_full_design"),
+            rate: number(supply, "current_now"),
+        }
+    }
+}
+
+fn percent(part: u64, whole: u64) -> Option<f64> {
+    (whole > 0).then(|| part as f64 * 100.0 / whole as f64)
+}
+
+fn hours(amount: u64, rate: u64) -> Option<Duration> {
+    (rate > 0).then(|| Duration::from_secs(amount * 3600 / rate))
+}
+
+fn battery(supply: &Path, name: String) -> Option<Battery> {
+    let Readings {
+        now,
+        full,
+        design,
+        rate,
+    } = readings(supply);
+
+    // peripherals sometimes only report `capacity_level`, which is no percentage
+    let capacity = attribute(supply, "capacity")
+        .and_then(|capacity| capacity.parse::<u8>().ok())
+        .or_else(|| Some(percent(now?, full?)?.round() as u8))?
+        .min(100);
+    let status = attribute(supply, "status")
+        .map(|status| BatteryStatus::from_sysfs(&status))
+        .unwrap_or(BatteryStatus::Unknown);
+
+    // drivers with their own estimate know better than a single current sample
+    let estimate = |attribute: &str| number(supply, attribute).map(Duration::from_secs);
+    let time_to_empty = match status {
+        BatteryStatus::Discharging => estimate("time_to_empty_now").or_else(|| hours(now?, rate?)),
+        _ => None,
+    };
+    let time_to_full = match status {
+        BatteryStatus::Charging => {
+            estimate("time_to_full_now").or_else(|| hours(full?.saturating_sub(now?), rate?))
+        }
+        _ => None,
+    };
+
+    Some(Battery {
+        model: attribute(supply, "model_name"),
+        capacity,
+        status,
+        peripheral: attribute(supply, "scope").as_deref() == Some("Device"),
+        time_to_empty,
+        time_to_full,
+        health: full
+            .zip(design)
+            .and_then(|(full, design)| percent(full, design)),
+        // unsupported by many drivers, which then report 0
+        cycle_count: number(supply, "cycle_count")
+            .filter(|&cycles| cycles > 0)
+            .map(|cycles| cycles as u32),
+        name,
+    })
+}
+
+/// `Ok(None)` when there is neither a battery nor an AC adapter, as on most desktops
+pub fn get_power() -> Result<Option<Power>> {
+    let mut ac_online = None;
+    let mut batteries = Vec::new();
+
+    for entry in read_dir(POWER_SUPPLY)?.flatten() {
+        let supply = entry.path();
+        let name = entry.file_name().to_string_lossy().into_owned();
+
+        match attribute(&supply, "type").as_deref() {
+            Some("Battery") => batteries.extend(battery(&supply, name)),
+            // USB-C chargers show up as "USB" next to, or instead of, "Mains"
+            Some("Mains" | "USB") if attribute(&supply, "scope").as_deref() != Some("Device") => {
+                if let Some(online) = attribute(&supply, "online") {
+                    *ac_online.get_or_insert(false) |= online == "1";
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if ac_online.is_none() && batteries.is_empty() {
+        return Ok(None);
+    }
+    batteries.sort_by(|a, b| (a.peripheral, &a.name).cmp(&(b.peripheral, &b.name)));
+    Ok(Some(Power {
+        ac_online,
+        batteries,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// A fake `/sys/class/power_supply/<name>` with the given attributes
+    fn supply(name: &str, attributes: &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("fortfetch-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        for (attribute, value) in attributes {
+            fs::write(dir.join(attribute), format!("{}\n", value)).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn energy_with_current_uses_the_voltage_for_power() {
+        // 30 Wh left at 2 A and 15 V, so 30 W: one hour
+        let dir = supply(
+            "energy-current",
+            &[
+                ("status", "Discharging"),
+                ("capacity", "60"),
+                ("energy_now", "30000000"),
+                ("energy_full", "50000000"),
+                ("current_now", "2000000"),
+                ("voltage_now", "15000000"),
+                ("charge_full_design", "4000000"),
+            ],
+        );
+        let battery = battery(&dir, "BAT0".to_string()).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(battery.time_to_empty, Some(Duration::from_secs(3600)));
+        // a charge design capacity is no match for an energy full capacity
+        assert_eq!(battery.health, None);
+    }
+
+    #[test]
+    fn energy_without_power_or_voltage_has_no_estimate() {
+        let dir = supply(
+            "energy-only",
+            &[
+                ("status", "Discharging"),
+                ("energy_now", "30000000"),
+                ("energy_full", "60000000"),
+                ("energy_full_design", "80000000"),
+                ("current_now", "2000000"),
+            ],
+        );
+        let battery = battery(&dir, "BAT0".to_string()).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(battery.capacity, 50);
+        assert_eq!(battery.time_to_empty, None);
+        assert_eq!(battery.health, Some(75.0));
+    }
+
+    #[test]
+    fn charge_family_pairs_with_current() {
+        // 1.5 Ah to go at 0.75 A: two hours
+        let dir = supply(
+            "charge",
+            &[
+                ("status", "Charging"),
+                ("charge_now", "2500000"),
+                ("charge_full", "4000000"),
+                ("charge_full_design", "5000000"),
+                ("current_now", "-750000"),
+                ("power_now", "11000000"),
+            ],
+        );
+        let battery = battery(&dir, "BAT1".to_string()).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(battery.time_to_full, Some(Duration::from_secs(7200)));
+        assert_eq!(battery.health, Some(80.0));
+    }
+}
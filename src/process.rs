@@ -0,0 +1,101 @@
+//! Process names and parents from `/proc/<pid>/stat`
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Interactive shells, as their processes are named
+pub(crate) const SHELLS: &[&str] = &[
+    "sh", "bash", "zsh", "fish", "dash", "ksh", "mksh", "oksh", "tcsh", "csh", "nu", "elvish",
+    "xonsh", "pwsh", "ion",
+];
+
+/// Programs that run another one on its behalf, which sit between a shell and its children
+pub(crate) const WRAPPERS: &[&str] = &[
+    "sudo",
+    "su",
+    "doas",
+    "run0",
+    "env",
+    "nice",
+    "time",
+    "script",
+    "fortfetch",
+];
+
+/// `comm` is cut to 15 bytes, so longer names like "gnome-terminal-server" need `/proc/<pid>/exe`
+const COMM_MAX: usize = 15;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Process {
+    pub pid: u32,
+    pub ppid: u32,
+    /// Executable name, e.g. "kitty"
+    pub name: String,
+}
+
+impl Process {
+    pub fn read(pid: u32) -> Option<Process> {
+        let dir = Path::new("/proc").join(pid.to_string());
+        let stat = fs::read_to_string(dir.join("stat")).ok()?;
+        // `1234 (comm) S 1200 ...`; comm may itself hold spaces and parentheses
+        let (_, rest) = stat.split_once(" (")?;
+        let (comm, fields) = rest.rsplit_once(") ")?;
+        let ppid = fields.split_whitespace().nth(1)?.parse().ok()?;
+
+        let name = if comm.len() < COMM_MAX {
+            comm.to_string()
+        } else {
+            full_name(&dir).unwrap_or_else(|| comm.to_string())
+        };
+        Some(Process { pid, ppid, name })
+    }
+
+    /// Arguments, starting with argv[0]; empty for kernel threads and other users' hidden processes
+    pub fn cmdline(&self) -> Vec<String> {
+        fs::read(format!("/proc/{}/cmdline", self.pid))
+            .unwrap_or_default()
+            .split(|&byte| byte == 0)
+            .filter(|arg| !arg.is_empty())
+            .map(|arg| String::from_utf8_lossy(arg).into_owned())
+            .collect()
+    }
+
+    /// Path of the executable; unreadable for other users' processes
+    pub fn exe(&self) -> Option<PathBuf> {
+        fs::read_link(format!("/proc/{}/exe", self.pid)).ok()
+    }
+
+    pub fn parent(&self) -> Option<Process> {
+        (self.ppid > 0).then(|| Process::read(self.ppid)).flatten()
+    }
+}
+
+/// The untruncated name, from the executable or, for other users' processes, from argv[0]
+fn full_name(dir: &Path) -> Option<String> {
+    let path = fs::read_link(dir.join("exe")).ok().or_else(|| {
+        let cmdline = fs::read(dir.join("cmdline")).ok()?;
+        let argv0 = cmdline.split(|&byte| byte == 0).next()?;
+        Some(String::from_utf8_lossy(argv0).into_owned().into())
+    })?;
+    let name = path.file_name()?.to_string_lossy();
+    // a login shell's argv[0] is "-bash"
+    Some(name.trim_start_matches('-').to_string()).filter(|name| !name.is_empty())
+}
+
+/// Our parent, its parent and so on up to init
+pub(crate) fn ancestors() -> impl Iterator<Item = Process> {
+    let parent = Process::read(std::process::id()).and_then(|process| process.parent());
+    std::iter::successors(parent, Process::parent)
+}
+
+/// Every process we can see
+pub(crate) fn all() -> Vec<Process> {
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_str()?.parse().ok())
+        .filter_map(Process::read)
+        .collect()
+}
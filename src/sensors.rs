@@ -0,0 +1,249 @@
+//! Temperatures from hwmon chips and ACPI/device-tree thermal zones
+
+use crate::error::{Error, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const HWMON: &str = "/sys/class/hwmon";
+const THERMAL: &str = "/sys/class/thermal";
+
+/// Sensors shown unless the config picks others
+pub const DEFAULT_SENSORS: &[&str] = &["cpu", "gpu", "nvme"];
+
+/// What a sensor measures, going by its driver or zone name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SensorKind {
+    Cpu,
+    Gpu,
+    Nvme,
+    /// Motherboard, ACPI, Wi-Fi and everything else
+    Other,
+}
+
+impl SensorKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            SensorKind::Cpu => "cpu",
+            SensorKind::Gpu => "gpu",
+            SensorKind::Nvme => "nvme",
+            SensorKind::Other => "other",
+        }
+    }
+
+    fn from_chip(chip: &str) -> SensorKind {
+        match chip {
+            "coretemp" | "k10temp" | "zenpower" | "x86_pkg_temp" | "cpu_thermal"
+            | "cpu-thermal" => SensorKind::Cpu,
+            "amdgpu" | "nouveau" | "radeon" | "gpu_thermal" | "gpu-thermal" => SensorKind::Gpu,
+            "nvme" => SensorKind::Nvme,
+            _ => SensorKind::Other,
+        }
+    }
+
+    /// The input worth showing when a chip has several, e.g. the package and not every core
+    fn preferred_labels(self, chip: &str) -> &'static [&'static str] {
+        match (self, chip) {
+            (_, "coretemp") => &["Package id"],
+            (_, "k10temp" | "zenpower") => &["Tdie", "Tctl"],
+            (_, "amdgpu") => &["edge"],
+            (SensorKind::Nvme, _) => &["Composite"],
+            _ => &[],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sensor {
+    pub kind: SensorKind,
+    /// hwmon `name` or thermal zone `type`, e.g. "k10temp" or "acpitz"
+    pub chip: String,
+    /// Input label, e.g. "Tctl", "Package id 0" or "Composite"
+    pub label: Option<String>,
+    /// Device behind the chip, e.g. "nvme0" or "0000:03:00.0", to tell two of a kind apart
+    pub device: Option<String>,
+    pub celsius: f64,
+    /// Where the hardware starts to throttle or warn
+    pub high: Option<f64>,
+    /// Where it shuts down
+    pub critical: Option<f64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SensorSettings {
+    /// Kinds ("cpu", "gpu", "nvme", "other") or chip names ("acpitz"), shown in this order
+    pub sensors: Vec<String>,
+}
+
+impl Default for SensorSettings {
+    fn default() -> Self {
+        SensorSettings {
+            sensors: DEFAULT_SENSORS
+                .iter()
+                .map(|sensor| sensor.to_string())
+                .collect(),
+        }
+    }
+}
+
+fn attribute(dir: &Path, name: &str) -> Option<String> {
+    let value = fs::read_to_string(dir.join(name)).ok()?;
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// Millidegrees as sysfs writes them; unset limits read as 0 or absurdly high values
+fn millidegrees(dir: &Path, name: &str) -> Option<f64> {
+    let value = attribute(dir, name)?.parse::<i64>().ok()? as f64 / 1000.0;
+    (value > 0.0 && value < 200.0).then_some(value)
+}
+
+fn device_name(dir: &Path) -> Option<String> {
+    let device = fs::canonicalize(dir.join("device")).ok()?;
+    Some(device.file_name()?.to_string_lossy().into_owned())
+}
+
+fn sorted_entries(dir: &str, prefix: &str) -> Vec<PathBuf> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with(prefix))
+        .map(|entry| entry.path())
+        .collect();
+    // hwmon10 after hwmon9
+    entries.sort_by_key(|path| {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let index: u32 = name[prefix.len()..].parse().unwrap_or(u32::MAX);
+        index
+    });
+    entries
+}
+
+/// One reading per chip from `/sys/class/hwmon/hwmonN/tempM_{input,label,max,crit}`
+fn hwmon_sensor(hwmon: &Path) -> Option<Sensor> {
+    // drivers from before Linux 3.x keep their attributes under `device/`
+    let dir = if hwmon.join("name").exists() {
+        hwmon.to_path_buf()
+    } else {
+        hwmon.join("device")
+    };
+    let chip = attribute(&dir, "name")?;
+    let kind = SensorKind::from_chip(&chip);
+
+    let mut inputs: Vec<(u32, Option<String>)> = fs::read_dir(&dir)
+        .ok()?
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let index = name
+                .strip_prefix("temp")?
+                .strip_suffix("_input")?
+                .parse()
+                .ok()?;
+            Some((index, attribute(&dir, &format!("temp{}_label", index))))
+        })
+        .collect();
+    inputs.sort();
+
+    let preferred = kind.preferred_labels(&chip).iter().find_map(|wanted| {
+        inputs.iter().find(|(_, label)| {
+            label
+                .as_deref()
+                .is_some_and(|label| label.starts_with(wanted))
+        })
+    });
+    let (index, label) = preferred.or_else(|| inputs.first())?.clone();
+    let temp = |suffix: &str| millidegrees(&dir, &format!("temp{}_{}", index, suffix));
+
+    Some(Sensor {
+        kind,
+        label,
+        device: device_name(&dir),
+        celsius: temp("input")?,
+        high: temp("max"),
+        critical: temp("crit"),
+        chip,
+    })
+}
+
+/// `/sys/class/thermal/thermal_zoneN`, with limits taken from its trip points
+fn zone_sensor(zone: &Path) -> Option<Sensor> {
+    let chip = attribute(zone, "type")?;
+
+    let trips: Vec<(String, f64)> = (0..)
+        .map_while(|index| {
+            let kind = attribute(zone, &format!("trip_point_{}_type", index))?;
+            Some((
+                kind,
+                millidegrees(zone, &format!("trip_point_{}_temp", index)),
+            ))
+        })
+        .filter_map(|(kind, temp)| Some((kind, temp?)))
+        .collect();
+    let trip = |wanted: &str| {
+        trips
+            .iter()
+            .find_map(|(kind, temp)| (kind == wanted).then_some(*temp))
+    };
+
+    Some(Sensor {
+        kind: SensorKind::from_chip(&chip),
+        label: None,
+        device: None,
+        celsius: millidegrees(zone, "temp")?,
+        // `passive` is where the kernel starts throttling, for zones without a `hot` trip
+        high: trip("hot").or_else(|| trip("passive")),
+        critical: trip("critical"),
+        chip,
+    })
+}
+
+/// Every sensor picked by the settings, in the order they are listed there
+pub fn get_sensors(settings: &SensorSettings) -> Result<Vec<Sensor>> {
+    let mut found: Vec<Sensor> = sorted_entries(HWMON, "hwmon")
+        .iter()
+        .filter_map(|hwmon| hwmon_sensor(hwmon))
+        .collect();
+
+    // most zones are also registered as hwmon chips under their type, and
+    // coretemp already covers `x86_pkg_temp`; zones only fill in what hwmon lacks
+    let hwmon_kinds: Vec<SensorKind> = found
+        .iter()
+        .map(|sensor| sensor.kind)
+        .filter(|&kind| kind != SensorKind::Other)
+        .collect();
+    let zones: Vec<Sensor> = sorted_entries(THERMAL, "thermal_zone")
+        .iter()
+        .filter_map(|zone| zone_sensor(zone))
+        .filter(|zone| {
+            !hwmon_kinds.contains(&zone.kind)
+                && !found.iter().any(|sensor| sensor.chip == zone.chip)
+        })
+        .collect();
+    found.extend(zones);
+
+    if found.is_empty() {
+        return Err(Error::NotFound(
+            "temperature sensor in hwmon or thermal zones",
+        ));
+    }
+
+    let mut picked = Vec::new();
+    for wanted in &settings.sensors {
+        for sensor in &found {
+            let matches = sensor.kind.name() == wanted || &sensor.chip == wanted;
+            if matches && !picked.contains(sensor) {
+                picked.push(sensor.clone());
+            }
+        }
+    }
+
+    if picked.is_empty() {
+        let chips: Vec<&str> = found.iter().map(|sensor| sensor.chip.as_str()).collect();
+        return Err(Error::parse(
+            HWMON,
+            format!("none of {} is a selected sensor", chips.join(", ")),
+        ));
+    }
+    Ok(picked)
+}
@@ -0,0 +1,61 @@
+//! The shell fortfetch was started from, which need not be the login shell in `$SHELL`
+
+use crate::error::{Result, env_var};
+use crate::process;
+use crate::version;
+use std::path::PathBuf;
+
+/// How to make a shell print its version and exit; others, like dash, have no such flag
+const VERSION_FLAGS: &[(&str, &str)] = &[
+    ("bash", "--version"),
+    ("zsh", "--version"),
+    ("fish", "--version"),
+    ("nu", "--version"),
+    ("tcsh", "--version"),
+    ("xonsh", "--version"),
+    ("elvish", "-version"),
+    ("pwsh", "--version"),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shell {
+    /// e.g. "zsh"
+    pub name: String,
+    /// e.g. "5.9"
+    pub version: Option<String>,
+}
+
+/// The nearest shell among our ancestors, looking past `sudo` and the like
+fn running_shell() -> Option<(String, Option<PathBuf>)> {
+    process::ancestors()
+        .take_while(|process| {
+            let name = process.name.as_str();
+            process::SHELLS.contains(&name) || process::WRAPPERS.contains(&name)
+        })
+        .find(|process| process::SHELLS.contains(&process.name.as_str()))
+        .map(|process| {
+            let exe = process.exe();
+            (process.name, exe)
+        })
+}
+
+pub fn get_shell() -> Result<Shell> {
+    // started by something other than a shell, e.g. a status bar, so show the login shell
+    let (name, executable) = match running_shell() {
+        Some(shell) => shell,
+        None => {
+            let path = env_var(&["SHELL"])?;
+            let name = path.rsplit('/').next().unwrap_or(&path).to_string();
+            (name, Some(PathBuf::from(path)))
+        }
+    };
+
+    let version = VERSION_FLAGS
+        .iter()
+        .find(|(shell, _)| *shell == name)
+        .and_then(|(_, flag)| {
+            let executable = executable.or_else(|| version::find_executable(&name))?;
+            version::probe(&executable, &[flag])
+        });
+    Ok(Shell { name, version })
+}
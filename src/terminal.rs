@@ -0,0 +1,178 @@
+//! The terminal fortfetch runs in, found by walking up the process tree
+
+use crate::error::{Error, Result, env_var};
+use crate::process::{self, Process};
+use std::env;
+use std::fs;
+
+/// Executable names, which their servers share with their clients
+const MULTIPLEXERS: &[&str] = &["tmux", "screen", "zellij"];
+
+const REMOTE: &[&str] = &["sshd", "sshd-session", "dropbear", "mosh-server"];
+const CONSOLE: &[&str] = &["login", "agetty", "getty", "mingetty", "systemd", "init"];
+
+/// Interpreters whose first script argument is the real program, e.g. terminator
+const INTERPRETERS: &[&str] = &["python", "python3", "perl", "node", "ruby"];
+
+/// Terminal emulators by executable name, and the names they go by
+const EMULATORS: &[(&str, &str)] = &[
+    ("kitty", "kitty"),
+    ("alacritty", "Alacritty"),
+    ("foot", "foot"),
+    ("footclient", "foot"),
+    ("wezterm-gui", "WezTerm"),
+    ("ghostty", "Ghostty"),
+    ("gnome-terminal-server", "GNOME Terminal"),
+    ("gnome-terminal", "GNOME Terminal"),
+    ("kgx", "GNOME Console"),
+    ("ptyxis-agent", "Ptyxis"),
+    ("blackbox", "Black Box"),
+    ("konsole", "Konsole"),
+    ("yakuake", "Yakuake"),
+    ("xfce4-terminal", "Xfce Terminal"),
+    ("mate-terminal", "MATE Terminal"),
+    ("lxterminal", "LXTerminal"),
+    ("qterminal", "QTerminal"),
+    ("terminator", "Terminator"),
+    ("tilix", "Tilix"),
+    ("tilda", "Tilda"),
+    ("guake", "Guake"),
+    ("terminology", "Terminology"),
+    ("sakura", "Sakura"),
+    ("cool-retro-term", "cool-retro-term"),
+    ("contour", "Contour"),
+    ("rio", "Rio"),
+    ("xterm", "xterm"),
+    ("uxterm", "xterm"),
+    ("st", "st"),
+    ("urxvt", "rxvt-unicode"),
+    ("urxvtd", "rxvt-unicode"),
+    ("rxvt", "rxvt"),
+    ("warp", "Warp"),
+    ("hyper", "Hyper"),
+    ("Hyper", "Hyper"),
+    ("code", "VS Code"),
+    ("codium", "VSCodium"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TerminalKind {
+    /// A graphical terminal emulator
+    Emulator,
+    /// A remote shell over SSH or mosh
+    Remote,
+    /// The kernel's virtual console
+    Console,
+}
+
+impl TerminalKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            TerminalKind::Emulator => "emulator",
+            TerminalKind::Remote => "remote",
+            TerminalKind::Console => "console",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Terminal {
+    /// e.g. "kitty", "GNOME Terminal", "SSH" or "tty2"
+    pub name: String,
+    pub kind: TerminalKind,
+    /// tmux, screen or zellij between the terminal and the shell
+    pub multiplexer: Option<String>,
+    /// `$TERM` as set, e.g. "xterm-256color"
+    pub term: Option<String>,
+}
+
+/// Product name of a known emulator, looking past interpreters running one as a script
+fn emulator_name(process: &Process) -> Option<String> {
+    let executable = if INTERPRETERS.contains(&process.name.as_str()) {
+        process
+            .cmdline()
+            .iter()
+            .skip(1)
+            .find(|arg| !arg.starts_with('-'))
+            .and_then(|script| script.rsplit('/').next())
+            .map(str::to_string)?
+    } else {
+        process.name.clone()
+    };
+
+    EMULATORS
+        .iter()
+        .find(|(name, _)| *name == executable)
+        .map(|(_, product)| product.to_string())
+}
+
+/// A client of the multiplexer server we came across; the server is detached from any terminal,
+/// but the client runs inside one
+fn multiplexer_client(server: &Process) -> Option<Process> {
+    process::all().into_iter().find(|process| {
+        process.name == server.name && process.pid != server.pid && process.ppid != server.pid
+    })
+}
+
+/// `tty2` when our standard streams are a virtual console
+fn console_name() -> Option<String> {
+    (0..3).find_map(|fd| {
+        let target = fs::read_link(format!("/proc/self/fd/{}", fd)).ok()?;
+        let name = target.to_string_lossy().strip_prefix("/dev/")?.to_string();
+        (name.starts_with("tty") && name[3..].chars().all(|c| c.is_ascii_digit())).then_some(name)
+    })
+}
+
+pub fn get_terminal() -> Result<Terminal> {
+    let term = env::var("TERM").ok().filter(|term| !term.is_empty());
+    let terminal = |name: String, kind, multiplexer| Terminal {
+        name,
+        kind,
+        multiplexer,
+        term: term.clone(),
+    };
+
+    let mut multiplexer: Option<String> = None;
+    let mut next = process::ancestors().next();
+    while let Some(process) = next {
+        let name = process.name.as_str();
+
+        if MULTIPLEXERS.contains(&name) {
+            multiplexer.get_or_insert_with(|| name.to_string());
+            next = multiplexer_client(&process)
+                .and_then(|client| client.parent())
+                .or_else(|| process.parent());
+            continue;
+        }
+        if REMOTE.contains(&name) {
+            return Ok(terminal(
+                "SSH".to_string(),
+                TerminalKind::Remote,
+                multiplexer,
+            ));
+        }
+        if CONSOLE.contains(&name) {
+            break;
+        }
+        if let Some(emulator) = emulator_name(&process) {
+            return Ok(terminal(emulator, TerminalKind::Emulator, multiplexer));
+        }
+        // shells, sudo, build tools and IDE task runners between us and the terminal
+        next = process.parent();
+    }
+
+    if let Some(tty) = console_name() {
+        return Ok(terminal(tty, TerminalKind::Console, multiplexer));
+    }
+    if env_var(&["SSH_CONNECTION", "SSH_TTY"]).is_ok() {
+        return Ok(terminal(
+            "SSH".to_string(),
+            TerminalKind::Remote,
+            multiplexer,
+        ));
+    }
+    // sandboxes like Flatpak hide the host's processes, but emulators often announce themselves
+    env_var(&["TERM_PROGRAM"])
+        .map(|name| terminal(name, TerminalKind::Emulator, multiplexer))
+        .map_err(|_| Error::NotFound("terminal emulator among parent processes"))
+}
@@ -0,0 +1,164 @@
+//! Versions of shells and desktops, asked from their executables once and then cached
+//!
+//! Starting `gnome-shell --version` takes longer than everything else fortfetch does, so the
+//! answers are kept in `$XDG_CACHE_HOME/fortfetch/versions`, keyed by the executable's path and
+//! modification time: a package update replaces the file and so misses the cache.
+
+use std::env;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+/// How long a probe may run, well inside the detector timeout so the name still makes it out
+const PROBE_TIMEOUT: Duration = Duration::from_millis(500);
+const PROBE_POLL: Duration = Duration::from_millis(5);
+
+/// Detectors run on several threads, and each rewrites the whole cache file
+static CACHE_LOCK: Mutex<()> = Mutex::new(());
+
+fn cache_path() -> Option<PathBuf> {
+    let dir = env::var_os("XDG_CACHE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| Some(Path::new(&env::var_os("HOME")?).join(".cache")))?;
+    Some(dir.join("fortfetch").join("versions"))
+}
+
+/// `path<TAB>mtime`, the start of a cache line
+fn cache_key(executable: &Path) -> Option<String> {
+    let modified = fs::metadata(executable)
+        .ok()?
+        .modified()
+        .ok()?
+        .duration_since(UNIX_EPOCH)
+        .ok()?;
+    Some(format!("{}\t{}", executable.display(), modified.as_secs()))
+}
+
+fn cached(key: &str) -> Option<String> {
+    let cache = fs::read_to_string(cache_path()?).ok()?;
+    cache
+        .lines()
+        .find_map(|line| line.strip_prefix(key)?.strip_prefix('\t'))
+        .map(str::to_string)
+}
+
+/// Replace any entry for the same executable; a failure only costs the next run a probe
+fn store(executable: &Path, key: &str, version: &str) -> Option<()> {
+    let path = cache_path()?;
+    let _lock = CACHE_LOCK.lock().ok()?;
+
+    let stale = format!("{}\t", executable.display());
+    let old = fs::read_to_string(&path).unwrap_or_default();
+    let mut cache: String = old
+        .lines()
+        .filter(|line| !line.starts_with(&stale))
+        .flat_map(|line| [line, "\n"])
+        .collect();
+    cache.push_str(&format!("{}\t{}\n", key, version));
+
+    // another fortfetch may be reading it right now, so swap in a complete file
+    fs::create_dir_all(path.parent()?).ok()?;
+    let partial = path.with_extension(std::process::id().to_string());
+    fs::write(&partial, cache).ok()?;
+    fs::rename(&partial, &path).ok()
+}
+
+/// The first version-looking word of the first line, e.g. "5.2.21" from
+/// "GNU bash, version 5.2.21(1)-release (x86_64-pc-linux-gnu)"
+pub(crate) fn parse_version(output: &str) -> Option<String> {
+    let line = output.lines().find(|line| !line.trim().is_empty())?;
+    line.split(|c: char| c.is_whitespace() || c == ',' || c == '/')
+        .find(|word| word.starts_with(|c: char| c.is_ascii_digit()))
+        .map(|word| {
+            word.chars()
+                .take_while(|c| c.is_ascii_alphanumeric() || *c == '.')
+                .collect()
+        })
+}
+
+/// `name` as the shell would find it in `$PATH`
+pub(crate) fn find_executable(name: &str) -> Option<PathBuf> {
+    env::split_paths(&env::var_os("PATH")?)
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// Run `executable args` and pick the version from what it prints, or take it from the cache.
+/// `None` when it takes longer than [`PROBE_TIMEOUT`]; the caller keeps the name without it
+pub(crate) fn probe(executable: &Path, args: &[&str]) -> Option<String> {
+    let key = cache_key(executable)?;
+    if let Some(version) = cached(&key) {
+        return Some(version);
+    }
+
+    let mut child = Command::new(executable)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .ok()?;
+    // a version line fits in the pipe buffer, so the child never blocks on us before exiting
+    let deadline = Instant::now() + PROBE_TIMEOUT;
+    while child.try_wait().ok()?.is_none() {
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return None;
+        }
+        thread::sleep(PROBE_POLL);
+    }
+    let (mut stdout, mut stderr) = (String::new(), String::new());
+    child.stdout.take()?.read_to_string(&mut stdout).ok()?;
+    child.stderr.take()?.read_to_string(&mut stderr).ok()?;
+
+    // some print it to stderr, like ksh and old versions of tcsh
+    let version = parse_version(&stdout).or_else(|| parse_version(&stderr))?;
+    store(executable, &key, &version);
+    Some(version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_version_takes_the_first_numeric_word() {
+        let bash = "GNU bash, version 5.2.21(1)-release (x86_64-pc-linux-gnu)\nCopyright";
+        assert_eq!(parse_version(bash).as_deref(), Some("5.2.21"));
+        assert_eq!(
+            parse_version("zsh 5.9 (x86_64-pc-linux-gnu)").as_deref(),
+            Some("5.9")
+        );
+        assert_eq!(
+            parse_version("fish, version 3.7.1").as_deref(),
+            Some("3.7.1")
+        );
+        assert_eq!(parse_version("GNOME Shell 46.0").as_deref(), Some("46.0"));
+    }
+
+    #[test]
+    fn parse_version_skips_blank_lines_and_splits_on_slashes() {
+        assert_eq!(
+            parse_version("\n  \ntcsh/6.24.10 (Astron)").as_deref(),
+            Some("6.24.10")
+        );
+        assert_eq!(parse_version("\n\n"), None);
+        assert_eq!(parse_version("no version here"), None);
+    }
+
+    #[test]
+    fn probe_gives_up_on_a_hanging_executable() {
+        let Some(sleep) = find_executable("sleep") else {
+            return;
+        };
+        let started = Instant::now();
+        assert_eq!(probe(&sleep, &["5"]), None);
+        assert!(started.elapsed() < PROBE_TIMEOUT * 2);
+    }
+}
@@ -0,0 +1,116 @@
+//! The running window manager or compositor and the kind of graphical session
+
+use crate::error::{Error, Result};
+use crate::process;
+use std::env;
+
+/// Process names of window managers and compositors, and how they call themselves
+const WINDOW_MANAGERS: &[(&str, &str)] = &[
+    ("kwin_wayland", "KWin"),
+    ("kwin_x11", "KWin"),
+    ("kwin", "KWin"),
+    ("gnome-shell", "Mutter"),
+    ("mutter", "Mutter"),
+    ("muffin", "Muffin"),
+    ("cinnamon", "Muffin"),
+    ("marco", "Marco"),
+    ("xfwm4", "Xfwm4"),
+    ("Hyprland", "Hyprland"),
+    ("sway", "sway"),
+    ("river", "river"),
+    ("niri", "niri"),
+    ("wayfire", "Wayfire"),
+    ("labwc", "labwc"),
+    ("hikari", "hikari"),
+    ("dwl", "dwl"),
+    ("cage", "Cage"),
+    ("weston", "Weston"),
+    ("gamescope", "gamescope"),
+    ("cosmic-comp", "COSMIC"),
+    ("i3", "i3"),
+    ("bspwm", "bspwm"),
+    ("openbox", "Openbox"),
+    ("fluxbox", "Fluxbox"),
+    ("awesome", "awesome"),
+    ("dwm", "dwm"),
+    ("xmonad", "xmonad"),
+    ("xmonad-x86_64-linux", "xmonad"),
+    ("herbstluftwm", "herbstluftwm"),
+    ("qtile", "Qtile"),
+    ("spectrwm", "spectrwm"),
+    ("icewm", "IceWM"),
+    ("fvwm", "FVWM"),
+    ("fvwm3", "FVWM"),
+    ("jwm", "JWM"),
+    ("leftwm", "LeftWM"),
+    ("enlightenment", "Enlightenment"),
+    ("compiz", "Compiz"),
+    ("budgie-wm", "Budgie"),
+    ("gala", "Gala"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionType {
+    X11,
+    Wayland,
+    /// A text console without any graphical session
+    Tty,
+}
+
+impl SessionType {
+    pub fn name(self) -> &'static str {
+        match self {
+            SessionType::X11 => "X11",
+            SessionType::Wayland => "Wayland",
+            SessionType::Tty => "TTY",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WindowManager {
+    /// e.g. "Hyprland" or "KWin"; `None` on a bare console or with an unknown one
+    pub name: Option<String>,
+    pub session: Option<SessionType>,
+}
+
+/// Wayland or X11 as logind and the display variables tell it
+fn session_type() -> Option<SessionType> {
+    let set = |name| env::var_os(name).is_some_and(|value| !value.is_empty());
+    match env::var("XDG_SESSION_TYPE").as_deref() {
+        Ok("wayland") => Some(SessionType::Wayland),
+        Ok("x11") => Some(SessionType::X11),
+        Ok("tty") => Some(SessionType::Tty),
+        // `unspecified` over SSH or from a cron job; the sockets are the next best thing
+        _ if set("WAYLAND_DISPLAY") => Some(SessionType::Wayland),
+        _ if set("DISPLAY") => Some(SessionType::X11),
+        _ => None,
+    }
+}
+
+fn window_manager_name() -> Option<String> {
+    let processes = process::all();
+    // earlier entries win when several are running, e.g. gnome-shell over a leftover i3
+    WINDOW_MANAGERS.iter().find_map(|(executable, name)| {
+        processes
+            .iter()
+            .any(|process| process.name == *executable)
+            .then(|| name.to_string())
+    })
+}
+
+pub fn get_window_manager() -> Result<WindowManager> {
+    let session = session_type();
+    // a window manager found on a text console belongs to another seat or user
+    let name = match session {
+        Some(SessionType::Tty) => None,
+        _ => window_manager_name(),
+    };
+    let wm = WindowManager { name, session };
+    if wm.name.is_none() && wm.session.is_none() {
+        return Err(Error::NotFound(
+            "known window manager among running processes",
+        ));
+    }
+    Ok(wm)
+}
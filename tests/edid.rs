@@ -0,0 +1,97 @@
+//! EDID parsing against the blobs in `tests/fixtures/edid`; see `generate.py` there
+
+use fortfetch::{Edid, parse_edid};
+use std::fs;
+use std::path::Path;
+
+fn fixture(name: &str) -> Vec<u8> {
+    fs::read(
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/edid")
+            .join(name),
+    )
+    .expect("fixture exists")
+}
+
+fn parse(name: &str) -> Edid {
+    parse_edid(&fixture(name)).expect("fixture is a valid EDID")
+}
+
+fn round(refresh: f64) -> f64 {
+    (refresh * 100.0).round() / 100.0
+}
+
+#[test]
+fn desktop_monitor() {
+    let edid = parse("dell-u2415.bin");
+    assert_eq!(edid.manufacturer_id, "DEL");
+    assert_eq!(edid.manufacturer(), "Dell");
+    assert_eq!(edid.product_code, 0xa0a4);
+    assert_eq!(edid.name.as_deref(), Some("DELL U2415"));
+    assert_eq!(edid.serial.as_deref(), Some("7MT0167B1ABL"));
+    assert_eq!(edid.year, 2016);
+    assert_eq!((edid.width_mm, edid.height_mm), (Some(518), Some(324)));
+
+    let mode = edid.preferred_mode().unwrap();
+    assert_eq!((mode.width, mode.height), (1920, 1200));
+    assert_eq!(round(mode.refresh), 59.95);
+    assert!(!mode.interlaced);
+}
+
+#[test]
+fn laptop_panel_without_name_descriptor() {
+    let edid = parse("boe-laptop-panel.bin");
+    assert_eq!(edid.manufacturer(), "BOE");
+    // the model from the last free-form text stands in for the missing name
+    assert_eq!(edid.name.as_deref(), Some("NV156FHM-N48"));
+    assert_eq!(edid.serial, None);
+    assert_eq!((edid.width_mm, edid.height_mm), (Some(344), Some(194)));
+
+    let mode = edid.preferred_mode().unwrap();
+    assert_eq!((mode.width, mode.height), (1920, 1080));
+    assert_eq!(round(mode.refresh), 59.93);
+}
+
+#[test]
+fn timings_from_cta_extension() {
+    let edid = parse("lg-hdr-4k.bin");
+    assert_eq!(edid.manufacturer_id, "GSM");
+    assert_eq!(edid.manufacturer(), "LG");
+    assert_eq!(edid.name.as_deref(), Some("LG HDR 4K"));
+    assert_eq!(edid.year, 2018);
+
+    let modes: Vec<_> = edid
+        .modes
+        .iter()
+        .map(|mode| {
+            (
+                mode.width,
+                mode.height,
+                round(mode.refresh),
+                mode.interlaced,
+            )
+        })
+        .collect();
+    assert_eq!(
+        modes,
+        [
+            (3840, 2160, 60.0, false),
+            (1920, 1080, 60.0, false),
+            (1920, 1080, 60.05, true),
+        ]
+    );
+}
+
+#[test]
+fn rejects_bad_checksum() {
+    let mut blob = fixture("dell-u2415.bin");
+    blob[20] ^= 0xff;
+    assert!(parse_edid(&blob).is_err());
+}
+
+#[test]
+fn rejects_truncated_and_foreign_data() {
+    let blob = fixture("dell-u2415.bin");
+    assert!(parse_edid(&blob[..100]).is_err());
+    assert!(parse_edid(&[0u8; 128]).is_err());
+}
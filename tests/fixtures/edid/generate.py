@@ -0,0 +1,115 @@
+#!/usr/bin/env python3
+"""Regenerate the EDID fixtures used by tests/edid.rs.
+
+Each blob is laid out byte for byte after the EDID 1.4 and CTA-861 specs, with
+the identification, sizes and timings of real monitors as edid-decode prints
+them. Serial numbers are made up.
+"""
+
+import os
+import struct
+
+HERE = os.path.dirname(os.path.abspath(__file__))
+
+
+def manufacturer(pnp):
+    """Three letters packed into five bits each, big-endian"""
+    value = 0
+    for letter in pnp:
+        value = value << 5 | (ord(letter) - ord("A") + 1)
+    return struct.pack(">H", value)
+
+
+def checksum(block):
+    return bytes([(256 - sum(block) % 256) % 256])
+
+
+def detailed_timing(clock_khz, h_active, h_blank, v_active, v_blank, width_mm, height_mm,
+                    interlaced=False):
+    h_sync_offset, h_sync_width, v_sync_offset, v_sync_width = 48, 32, 3, 6
+    return bytes([
+        (clock_khz // 10) & 0xff, (clock_khz // 10) >> 8,
+        h_active & 0xff, h_blank & 0xff, (h_active >> 8) << 4 | h_blank >> 8,
+        v_active & 0xff, v_blank & 0xff, (v_active >> 8) << 4 | v_blank >> 8,
+        h_sync_offset, h_sync_width, v_sync_offset << 4 | v_sync_width, 0,
+        width_mm & 0xff, height_mm & 0xff, (width_mm >> 8) << 4 | height_mm >> 8,
+        0, 0, 0x18 | (0x80 if interlaced else 0),
+    ])
+
+
+def text_descriptor(tag, text):
+    payload = text.encode("ascii")
+    if len(payload) < 13:
+        payload += b"\n" + b" " * (12 - len(payload))
+    return bytes([0, 0, 0, tag, 0]) + payload
+
+
+def range_limits():
+    return bytes([0, 0, 0, 0xfd, 0, 56, 76, 30, 83, 17, 0, 0x0a]) + b" " * 6
+
+
+def dummy():
+    return bytes([0, 0, 0, 0x10]) + bytes(14)
+
+
+def base_block(pnp, product, serial, week, year, width_cm, height_cm, descriptors, extensions=0):
+    assert len(descriptors) == 4
+    block = b"\x00\xff\xff\xff\xff\xff\xff\x00"
+    block += manufacturer(pnp) + struct.pack("<HI", product, serial)
+    block += bytes([week, year - 1990, 1, 4])
+    block += bytes([0xa5, width_cm, height_cm, 120, 0x3a])
+    block += bytes(10)  # chromaticity
+    block += bytes(3)  # established timings
+    block += b"\x01\x01" * 8  # no standard timings
+    block += b"".join(descriptors)
+    block += bytes([extensions])
+    assert len(block) == 127
+    return block + checksum(block)
+
+
+def cta_block(timings):
+    """CTA-861 extension with no data blocks, only detailed timings"""
+    block = bytes([0x02, 0x03, 4, 0x70]) + b"".join(timings)
+    block += bytes(127 - len(block))
+    return block + checksum(block)
+
+
+def write(name, data):
+    with open(os.path.join(HERE, name), "wb") as f:
+        f.write(data)
+
+
+def main():
+    # DELL U2415: 24" 16:10, 1920x1200 at 59.95 Hz (CVT reduced blanking)
+    write("dell-u2415.bin", base_block(
+        "DEL", 0xa0a4, 0x4c4d3130, 12, 2016, 52, 32, [
+            detailed_timing(154000, 1920, 160, 1200, 35, 518, 324),
+            text_descriptor(0xff, "7MT0167B1ABL"),
+            text_descriptor(0xfc, "DELL U2415"),
+            range_limits(),
+        ]))
+
+    # BOE NV156FHM-N48: 15.6" laptop panel, names itself only in free-form text
+    write("boe-laptop-panel.bin", base_block(
+        "BOE", 0x0731, 0, 1, 2019, 34, 19, [
+            detailed_timing(138500, 1920, 160, 1080, 31, 344, 194),
+            dummy(),
+            text_descriptor(0xfe, "BOE HF"),
+            text_descriptor(0xfe, "NV156FHM-N48"),
+        ]))
+
+    # LG HDR 4K: 27" UHD, 3840x2160 at 60 Hz, 1080i and 1080p timings in a CTA-861 block
+    write("lg-hdr-4k.bin", base_block(
+        "GSM", 0x7706, 0x0003e3a8, 255, 2018, 60, 34, [
+            detailed_timing(533250, 3840, 160, 2160, 62, 600, 340),
+            range_limits(),
+            text_descriptor(0xfc, "LG HDR 4K"),
+            text_descriptor(0xff, "808NTLE1J812"),
+        ], extensions=1) + cta_block([
+            detailed_timing(148500, 1920, 280, 1080, 45, 600, 340),
+            detailed_timing(74250, 1920, 280, 540, 22, 600, 340, interlaced=True),
+        ]))
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,167 @@
+#!/usr/bin/env python3
+"""Regenerate the rpmdb fixtures used by tests/rpmdb.rs.
+
+sqlite/rpmdb.sqlite is a real SQLite database with rpm's schema. The NDB and
+Berkeley DB files are laid out by hand following rpm's lib/backend/ndb/rpmpkg.c
+and Berkeley DB's hash page format, since neither library is commonly installed.
+Package headers are replaced with filler: the counters never read them.
+
+The malformed/ fixtures are those files with one header field broken, each of
+which must be reported as a parse error rather than crash the counter.
+"""
+
+import os
+import sqlite3
+import struct
+
+HERE = os.path.dirname(os.path.abspath(__file__))
+
+
+def sqlite_fixture(path):
+    """100 packages, 7 of them erased again, one too big for its page"""
+    if os.path.exists(path):
+        os.remove(path)
+    db = sqlite3.connect(path)
+    db.execute("PRAGMA page_size = 512")
+    db.execute(
+        "CREATE TABLE 'Packages' (hnum INTEGER PRIMARY KEY AUTOINCREMENT, blob BLOB NOT NULL)"
+    )
+    for index in ["Name", "Basenames", "Providename", "Requirename", "Group", "Sha1header"]:
+        db.execute(
+            f"CREATE TABLE '{index}' (key TEXT NOT NULL, hnum INTEGER NOT NULL, "
+            f"idx INTEGER NOT NULL, FOREIGN KEY (hnum) REFERENCES 'Packages'(hnum))"
+        )
+        db.execute(f"CREATE INDEX '{index}_key_idx' ON '{index}'(key ASC)")
+
+    for hnum in range(1, 101):
+        size = 2000 if hnum == 50 else 200
+        db.execute("INSERT INTO Packages (blob) VALUES (?)", (bytes([hnum]) * size,))
+        db.execute("INSERT INTO Name VALUES (?, ?, 0)", (f"package-{hnum}", hnum))
+    db.execute("DELETE FROM Packages WHERE hnum IN (3, 17, 42, 50, 64, 88, 99)")
+    db.commit()
+    db.execute("VACUUM")
+    db.close()
+
+
+def ndb_fixture(path):
+    """5 packages in a single slot page, with a freed slot between them"""
+    page_size, slot_size, header_size = 4096, 16, 32
+    used = {0: 1, 1: 2, 3: 3, 4: 4, 7: 5}  # slot number -> package index
+
+    header = struct.pack("<4sIIII", b"RpmP", 0, 1, 1, 6).ljust(header_size, b"\0")
+    slots = b""
+    for slot in range((page_size - header_size) // slot_size):
+        if slot in used:
+            block = page_size // 16 + used[slot] * 4
+            slots += struct.pack("<4sIII", b"Slot", used[slot], block, 4)
+        else:
+            slots += struct.pack("<4sIII", b"Slot", 0, 0, 0)
+
+    blobs = b"".join(struct.pack("<4sII", b"BlbS", i, 1).ljust(64, b"\xaa") for i in range(1, 6))
+    with open(path, "wb") as f:
+        f.write(header + slots + blobs)
+
+
+def bdb_page(number, page_type, items, page_size):
+    """Berkeley DB page: 26-byte header, item offsets, items packed from the end"""
+    offsets, data, end = [], b"", page_size
+    for item in items:
+        end -= len(item)
+        offsets.append(end)
+        data = item + data
+    header = struct.pack("<8sIIIHHBB", b"\0" * 8, number, 0, 0, len(items), end, 0, page_type)
+    body = header + b"".join(struct.pack("<H", offset) for offset in offsets)
+    return body.ljust(end, b"\0") + data
+
+
+def bdb_fixture(path):
+    """6 packages over two hash pages plus rpm's key-0 bookkeeping record"""
+    page_size = 512
+    hash_page, overflow_page, hash_meta = 13, 7, 8
+    keydata, offpage = 1, 3
+
+    def key(instance):
+        return struct.pack("<BI", keydata, instance)
+
+    def header_on(page):
+        return struct.pack("<B3xII", offpage, page, 3000)
+
+    pages = [
+        bdb_page(1, hash_page, [key(0), struct.pack("<BI", keydata, 7),
+                                key(1), header_on(3), key(2), header_on(3), key(3), header_on(3)],
+                 page_size),
+        bdb_page(2, hash_page, [key(4), header_on(3), key(5), header_on(3), key(6), header_on(3)],
+                 page_size),
+        bdb_page(3, overflow_page, [b"\xbb" * 100], page_size),
+    ]
+
+    meta = struct.pack("<8sIIIIBBBBIIIII", b"\0" * 8, 0, 0x061561, 9, page_size, 0, hash_meta,
+                       0, 0, 0, len(pages), 0, 0, 0)
+    with open(path, "wb") as f:
+        f.write(meta.ljust(page_size, b"\0") + b"".join(pages))
+
+
+def patched(source, path, offset, value):
+    """A copy of `source` with `value` written over the bytes at `offset`"""
+    with open(source, "rb") as f:
+        data = bytearray(f.read())
+    data[offset:offset + len(value)] = value
+    with open(path, "wb") as f:
+        f.write(data)
+
+
+def single_table_sqlite(path):
+    """A database small enough for sqlite_master to be a single leaf page"""
+    if os.path.exists(path):
+        os.remove(path)
+    db = sqlite3.connect(path)
+    db.execute("PRAGMA page_size = 512")
+    db.execute("CREATE TABLE 'Packages' (hnum INTEGER PRIMARY KEY, blob BLOB NOT NULL)")
+    db.commit()
+    db.close()
+
+
+def malformed_fixtures(valid):
+    def out(name, file):
+        os.makedirs(os.path.join(HERE, "malformed", name), exist_ok=True)
+        return os.path.join(HERE, "malformed", name, file)
+
+    sqlite = os.path.join(valid, "sqlite", "rpmdb.sqlite")
+    # the page size is a big-endian u16 at 16, the reserved bytes per page at 20
+    patched(sqlite, out("sqlite-zero-page-size", "rpmdb.sqlite"), 16, b"\0\0")
+    path = out("sqlite-reserved-bytes-over-page-size", "rpmdb.sqlite")
+    patched(sqlite, path, 16, struct.pack(">H", 16))
+    patched(path, path, 20, bytes([64]))
+    # SQLite never writes pages this small, so this one is assembled by hand: the file header,
+    # then page 1 as a table leaf with one cell whose payload must be sized against 16 usable bytes
+    with open(sqlite, "rb") as f:
+        header = bytearray(f.read(100))
+    header[16:18] = struct.pack(">H", 256)
+    header[20] = 240
+    cell = bytes([5, 1, 2, 1, 0])
+    leaf = struct.pack(">BHHHB", 13, 0, 1, 256 - len(cell), 0) + struct.pack(">H", 256 - len(cell))
+    page = bytes(header) + leaf
+    with open(out("sqlite-reserved-bytes-over-payload", "rpmdb.sqlite"), "wb") as f:
+        f.write(page.ljust(256 - len(cell), b"\0") + cell)
+    patched(sqlite, out("sqlite-reserved-bytes", "rpmdb.sqlite"), 20, b"\xff")
+
+    # the first sqlite_master cell: payload length, rowid, header length, then `type`'s serial
+    path = out("sqlite-reserved-serial-type", "rpmdb.sqlite")
+    single_table_sqlite(path)
+    with open(path, "rb") as f:
+        page = f.read(512)
+    (cell,) = struct.unpack(">H", page[108:110])
+    patched(path, path, cell + 3, bytes([10]))
+
+    # the slot page count is a little-endian u32 at 12
+    ndb = os.path.join(valid, "ndb", "Packages.db")
+    patched(ndb, out("ndb-no-slot-pages", "Packages.db"), 12, struct.pack("<I", 0))
+
+
+if __name__ == "__main__":
+    for name in ["sqlite", "ndb", "bdb"]:
+        os.makedirs(os.path.join(HERE, name), exist_ok=True)
+    sqlite_fixture(os.path.join(HERE, "sqlite", "rpmdb.sqlite"))
+    ndb_fixture(os.path.join(HERE, "ndb", "Packages.db"))
+    bdb_fixture(os.path.join(HERE, "bdb", "Packages"))
+    malformed_fixtures(HERE)
@@ -0,0 +1,69 @@
+//! Package counting against the rpmdb fixtures; see `tests/fixtures/rpmdb/generate.py`
+
+use fortfetch::count_rpmdb;
+use std::path::Path;
+
+fn fixture(format: &str) -> std::path::PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/rpmdb")
+        .join(format)
+}
+
+#[test]
+fn sqlite() {
+    assert_eq!(count_rpmdb(fixture("sqlite")), Ok(93));
+}
+
+#[test]
+fn ndb() {
+    assert_eq!(count_rpmdb(fixture("ndb")), Ok(5));
+}
+
+#[test]
+fn berkeley_db() {
+    assert_eq!(count_rpmdb(fixture("bdb")), Ok(6));
+}
+
+#[test]
+fn missing_database() {
+    assert!(count_rpmdb(fixture("")).is_err());
+}
+
+/// Each broken header must come back as a parse error, not a panic
+fn malformed(name: &str) -> fortfetch::Error {
+    let dir = fixture("malformed").join(name);
+    match count_rpmdb(&dir) {
+        Err(err @ fortfetch::Error::Parse { .. }) => err,
+        other => panic!("{}: expected a parse error, got {:?}", name, other),
+    }
+}
+
+#[test]
+fn sqlite_zero_page_size() {
+    malformed("sqlite-zero-page-size");
+}
+
+#[test]
+fn sqlite_reserved_bytes_over_page_size() {
+    malformed("sqlite-reserved-bytes-over-page-size");
+}
+
+#[test]
+fn sqlite_reserved_bytes_over_payload_limit() {
+    malformed("sqlite-reserved-bytes-over-payload");
+}
+
+#[test]
+fn sqlite_reserved_bytes_leave_no_usable_page() {
+    malformed("sqlite-reserved-bytes");
+}
+
+#[test]
+fn sqlite_reserved_serial_type() {
+    malformed("sqlite-reserved-serial-type");
+}
+
+#[test]
+fn ndb_without_slot_pages() {
+    malformed("ndb-no-slot-pages");
+}